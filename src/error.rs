@@ -1,5 +1,10 @@
-use std::io;
+use std::{
+    fmt, io,
+    ops::Range,
+    path::{Path, PathBuf},
+};
 
+use ariadne::{Config, IndexType, Label, Report, ReportKind, Source};
 use thiserror::Error;
 
 pub type Result<T> = ::std::result::Result<T, Error>;
@@ -15,13 +20,218 @@ pub enum Error {
     #[error("{0}")]
     TomlSer(#[from] toml::ser::Error),
 
+    #[error("{0}")]
+    Yaml(#[from] serde_yaml_ng::Error),
+
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("{0}")]
+    Template(#[from] minijinja::Error),
+
+    #[error("theme not found: {0}")]
+    ThemeNotFound(String),
+
+    #[error("highlight theme not found: {0}")]
+    HighlightThemeNotFound(String),
+
+    #[error("{0}")]
+    Watch(#[from] notify::Error),
+
     #[error("render not found: {0}")]
     RenderNotFound(String),
 
-    #[error("invalid data block")]
-    InvalidDataBlock,
+    #[error("invalid data block: {0}")]
+    InvalidDataBlock(Box<Diagnostic>),
+
+    #[error("invalid meta: {0}")]
+    InvalidMeta(Box<Diagnostic>),
+
+    #[error("invalid permalink: {0}")]
+    InvalidPermalink(String),
+
+    #[error("permalink `{url}` is taken by {}", by.display())]
+    PermalinkTaken { url: String, by: PathBuf },
+
+    #[error("{list} at `{url}` is taken by {}", by.display())]
+    ListTaken {
+        list: String,
+        url: String,
+        by: PathBuf,
+    },
+
+    #[error("asset `{}` is not a file", .0.display())]
+    InvalidAsset(PathBuf),
+
+    #[error("failed building {0} entries")]
+    BuildFailed(usize),
 
     #[default]
     #[error("unknown error")]
     Unknown,
 }
+
+impl Error {
+    /// Attach `path` of source file to located error.
+    pub(crate) fn at<P>(self, path: P) -> Error
+    where
+        P: AsRef<Path>,
+    {
+        match self {
+            Error::InvalidDataBlock(mut diagnostic) => {
+                diagnostic.path = path.as_ref().to_path_buf();
+                Error::InvalidDataBlock(diagnostic)
+            }
+            Error::InvalidMeta(mut diagnostic) => {
+                diagnostic.path = path.as_ref().to_path_buf();
+                Error::InvalidMeta(diagnostic)
+            }
+            e => e,
+        }
+    }
+
+    /// Annotated source snippet of located error.
+    pub(crate) fn snippet(&self, color: bool) -> Option<String> {
+        match self {
+            Error::InvalidDataBlock(diagnostic) | Error::InvalidMeta(diagnostic) => {
+                Some(diagnostic.snippet(color))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Error pointing at a span of source file.
+#[derive(Debug)]
+pub(crate) struct Diagnostic {
+    pub(crate) path: PathBuf,
+
+    /// Whole content of source file.
+    pub(crate) source: String,
+
+    /// Byte range inside `source`.
+    pub(crate) span: Range<usize>,
+
+    pub(crate) message: String,
+
+    pub(crate) label: String,
+}
+
+impl Diagnostic {
+    pub(crate) fn new<M, L>(source: &str, span: Range<usize>, message: M, label: L) -> Diagnostic
+    where
+        M: Into<String>,
+        L: Into<String>,
+    {
+        // Snap to characters, span inside one can't be shown.
+        let end = ceil_char_boundary(source, span.end);
+        let start = floor_char_boundary(source, span.start.min(end));
+
+        Diagnostic {
+            path: PathBuf::new(),
+            source: source.to_owned(),
+            span: start..end,
+            message: message.into(),
+            label: label.into(),
+        }
+    }
+
+    /// Locate TOML error of document embedded in `source` at `offset`.
+    pub(crate) fn from_toml(source: &str, offset: usize, e: &toml::de::Error) -> Diagnostic {
+        let span = e
+            .span()
+            .map(|it| it.start + offset..it.end + offset)
+            .unwrap_or(offset..offset);
+
+        Diagnostic::new(source, span, e.message(), e.message())
+    }
+
+    /// Line and column of span start, both 1-based.
+    pub(crate) fn position(&self) -> (usize, usize) {
+        let before = &self.source[..self.span.start];
+        let line = before.matches('\n').count() + 1;
+        let column = before
+            .rsplit_once('\n')
+            .map(|(_, it)| it)
+            .unwrap_or(before)
+            .chars()
+            .count()
+            + 1;
+
+        (line, column)
+    }
+
+    pub(crate) fn snippet(&self, color: bool) -> String {
+        let id = self.path.display().to_string();
+        let mut output = Vec::new();
+
+        let _ = Report::build(ReportKind::Error, (id.clone(), self.span.clone()))
+            .with_config(
+                Config::default()
+                    .with_color(color)
+                    .with_index_type(IndexType::Byte),
+            )
+            .with_message(&self.message)
+            .with_label(Label::new((id.clone(), self.span.clone())).with_message(&self.label))
+            .finish()
+            .write((id, Source::from(&self.source)), &mut output);
+
+        String::from_utf8_lossy(&output).into_owned()
+    }
+}
+
+fn floor_char_boundary(source: &str, index: usize) -> usize {
+    let mut index = index.min(source.len());
+    while !source.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(source: &str, index: usize) -> usize {
+    let mut index = index.min(source.len());
+    while !source.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (line, column) = self.position();
+        write!(
+            f,
+            "{}:{line}:{column}: {}",
+            self.path.display(),
+            self.message
+        )
+    }
+}
+
+/// Phase of building where an entry failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Phase {
+    Parse,
+    Permalink,
+    Render,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::Parse => write!(f, "parse"),
+            Phase::Permalink => write!(f, "permalink"),
+            Phase::Render => write!(f, "render"),
+        }
+    }
+}
+
+/// Failure of building a single entry.
+#[derive(Debug, Error)]
+#[error("{phase} failed: {cause}")]
+pub(crate) struct BuildFailure {
+    pub(crate) phase: Phase,
+
+    #[source]
+    pub(crate) cause: Error,
+}
@@ -1,20 +1,32 @@
-use std::{env, io, path::PathBuf};
+use std::{
+    env, io,
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+    process,
+};
 
 use clap::{arg, value_parser, ArgAction, ArgMatches, Command};
 use clap_complete::{generate, Generator, Shell};
 use human_panic::setup_panic;
+use itertools::Itertools;
 use log::error;
+use mkentry::Publishing;
 use proj::MoxideProj;
+use serve::DevServer;
 use util::CreateType;
 
-mod builder;
 mod error;
 mod manifest;
 mod mkentry;
+mod permalink;
 mod proj;
-mod property;
 mod render;
+mod search;
+mod serve;
+mod site;
+mod theme;
 mod util;
+mod vfs;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -30,14 +42,15 @@ async fn main() -> anyhow::Result<()> {
 
     let result = match matches.subcommand() {
         Some(("build", cmd)) => subcommand_build(cmd).await,
-        Some(("serve", cmd)) => subcommand_serve(cmd),
+        Some(("serve", cmd)) => subcommand_serve(cmd).await,
         Some(("new", cmd)) => subcommand_create(cmd),
         Some(("completion", cmd)) => subcommand_completion(cmd),
         _ => Ok(()),
     };
 
     if let Err(e) = result {
-        error!("{e}")
+        error!("{e}");
+        process::exit(1);
     }
 
     Ok(())
@@ -45,39 +58,44 @@ async fn main() -> anyhow::Result<()> {
 
 fn app() -> Command {
     Command::new("moxide")
-        .args([arg!(-p --property "Inject property").action(ArgAction::Append)])
-        .subcommand(
-            Command::new("build")
-                .about("Build Moxide project")
-                .args([arg!(--out "Output directory").action(ArgAction::Set)]),
-        )
+        .subcommand(Command::new("build").about("Build Moxide project").args([
+            arg!(--out <DIR> "Output directory").action(ArgAction::Set),
+            arg!(--drafts "Build drafts too"),
+            arg!(--future "Build entries dated in the future too"),
+        ]))
         .subcommand(
             Command::new("serve")
                 .about("Serve Moxide with live reloading")
                 .args([
-                    arg!(--port "Listening port").default_value("8000"),
-                    arg!(--addr "Listening addr").default_value("0.0.0.0"),
+                    arg!(--port <PORT> "Listening port")
+                        .value_parser(value_parser!(u16))
+                        .default_value("8000"),
+                    arg!(--addr <ADDR> "Listening addr")
+                        .value_parser(value_parser!(IpAddr))
+                        .default_value("0.0.0.0"),
                 ]),
         )
         .subcommand(
             Command::new("new")
                 .about("Create new project/page/bundle")
                 .args([
-                    arg!(--name "Created with name")
+                    arg!(<name> "Created with name")
                         .action(ArgAction::Set)
-                        .index(1)
                         .required(true),
-                    arg!(--type "Which you want to create")
+                    arg!(--type <TYPE> "Which you want to create")
                         .action(ArgAction::Set)
                         .value_parser(value_parser!(CreateType))
                         .default_value("page"),
+                    arg!(--asset <FILE> "Asset copied into created bundle")
+                        .action(ArgAction::Append)
+                        .value_parser(value_parser!(PathBuf)),
                 ]),
         )
         .subcommand(
             Command::new("completion")
                 .about("Generate shell completion")
                 .args([
-                    arg!(--shell "Which shell's completion you want to generate")
+                    arg!(--shell <SHELL> "Which shell's completion you want to generate")
                         .action(ArgAction::Set)
                         .value_parser(value_parser!(Shell)),
                 ]),
@@ -86,13 +104,18 @@ fn app() -> Command {
 
 async fn subcommand_build(matches: &ArgMatches) -> anyhow::Result<()> {
     let output = matches
-        .try_get_one::<String>("output")?
+        .try_get_one::<String>("out")?
         .cloned()
         .unwrap_or("./output".to_owned());
 
     let proj_path = PathBuf::from_iter(["./manifest.toml"]);
     let mut proj = MoxideProj::try_new(proj_path)?;
     proj.set_output(output);
+    proj.set_publishing(Publishing {
+        drafts: matches.get_flag("drafts"),
+        future: matches.get_flag("future"),
+        ..Publishing::default()
+    });
     proj.build().await?;
 
     Ok(())
@@ -112,7 +135,17 @@ fn subcommand_create(matches: &ArgMatches) -> anyhow::Result<()> {
 
             proj.create_page(name)?;
         }
-        Some(CreateType::Bundle) => {}
+        Some(CreateType::Bundle) => {
+            let proj_path = PathBuf::from_iter(["./manifest.toml"]);
+            let proj = MoxideProj::try_new(proj_path)?;
+
+            let assets = matches
+                .get_many::<PathBuf>("asset")
+                .map(|it| it.collect_vec())
+                .unwrap_or_default();
+
+            proj.create_bundle(name, &assets)?;
+        }
         _ => {
             todo!()
         }
@@ -121,7 +154,15 @@ fn subcommand_create(matches: &ArgMatches) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn subcommand_serve(matches: &ArgMatches) -> anyhow::Result<()> {
+async fn subcommand_serve(matches: &ArgMatches) -> anyhow::Result<()> {
+    let port = matches.get_one::<u16>("port").copied().unwrap();
+    let addr = matches.get_one::<IpAddr>("addr").copied().unwrap();
+
+    let proj_path = PathBuf::from_iter(["./manifest.toml"]);
+    DevServer::new(proj_path, SocketAddr::new(addr, port))
+        .run()
+        .await?;
+
     Ok(())
 }
 
@@ -137,3 +178,13 @@ fn subcommand_completion(matches: &ArgMatches) -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app() {
+        app().debug_assert();
+    }
+}
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::{permalink::SlugStyle, site::Section};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub(crate) struct Manifest {
     pub(crate) site: String,
@@ -7,10 +9,74 @@ pub(crate) struct Manifest {
     #[serde(default = "default_description")]
     pub(crate) description: String,
 
+    /// Address site is published at, such as `https://example.com`, making links of
+    /// feeds absolute.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub(crate) base_url: String,
+
     #[serde(default = "default_theme")]
     #[serde(skip_serializing_if = "String::is_empty")]
     pub(crate) theme: String,
 
+    /// Permalink pattern of entries, see [`crate::permalink::Permalink`].
+    #[serde(default = "default_permalink")]
+    pub(crate) permalink: String,
+
+    /// How slugs are made from titles, for permalinks, new entry directories and ids of
+    /// headings.
+    #[serde(default)]
+    pub(crate) slug: SlugStyle,
+
+    /// Pages listed on every page of lists, unless overridden by section or taxonomy.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) paginate_by: Option<usize>,
+
+    /// Which pages home page lists.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HomeConfig::is_default")]
+    pub(crate) home: HomeConfig,
+
+    /// Feeds of site, and of sections or taxonomies asking for them.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "FeedConfig::is_default")]
+    pub(crate) feed: FeedConfig,
+
+    /// Publish `sitemap.xml` listing every page.
+    #[serde(default = "default_true")]
+    #[serde(skip_serializing_if = "is_true")]
+    pub(crate) sitemap: bool,
+
+    /// Rules of `robots.txt`.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "RobotsConfig::is_default")]
+    pub(crate) robots: RobotsConfig,
+
+    /// Full-text search index of pages.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "SearchConfig::is_default")]
+    pub(crate) search: SearchConfig,
+
+    /// Syntax highlighting of fenced code blocks.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "HighlightConfig::is_default")]
+    pub(crate) highlight: HighlightConfig,
+
+    /// Extensions of Markdown, which entries may override by `markdown` of their meta.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "MarkdownOptions::is_default")]
+    pub(crate) markdown: MarkdownOptions,
+
+    /// Table of contents and ids of headings.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "TocConfig::is_default")]
+    pub(crate) toc: TocConfig,
+
+    /// Taxonomies grouping entries by terms, only `tags` if missing.
+    #[serde(default = "default_taxonomies")]
+    pub(crate) taxonomies: Vec<TaxonomyConfig>,
+
     #[serde(default = "Vec::new")]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub(crate) renders: Vec<String>,
@@ -20,10 +86,367 @@ fn default_description() -> String {
     "".to_owned()
 }
 
+fn default_permalink() -> String {
+    "/:year/:month/:slug/".to_owned()
+}
+
 fn default_theme() -> String {
     "".to_owned()
 }
 
+pub(crate) fn default_taxonomies() -> Vec<TaxonomyConfig> {
+    vec![TaxonomyConfig::new("tags")]
+}
+
+/// Taxonomy declared in manifest, terms of entries come from `taxonomies.<name>` of
+/// their meta, and from `tag` as well for `tags`.
+///
+/// Unknown keys are rejected, so top-level keys written after `[[taxonomies]]` by mistake
+/// aren't swallowed silently.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct TaxonomyConfig {
+    pub(crate) name: String,
+
+    /// Template listing every term.
+    #[serde(default = "default_taxonomy_template")]
+    #[serde(skip_serializing_if = "is_default_taxonomy_template")]
+    pub(crate) template: String,
+
+    /// Template listing pages of a single term.
+    #[serde(default = "default_term_template")]
+    #[serde(skip_serializing_if = "is_default_term_template")]
+    pub(crate) term_template: String,
+
+    /// Pages listed on every page of a term, `paginate_by` of manifest if missing.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) paginate_by: Option<usize>,
+
+    /// Publish a feed for every term.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) feed: bool,
+}
+
+impl TaxonomyConfig {
+    pub(crate) fn new<T>(name: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            name: name.into(),
+            template: default_taxonomy_template(),
+            term_template: default_term_template(),
+            paginate_by: None,
+            feed: false,
+        }
+    }
+}
+
+fn default_taxonomy_template() -> String {
+    "taxonomy.html".to_owned()
+}
+
+fn is_default_taxonomy_template(template: &str) -> bool {
+    template == default_taxonomy_template()
+}
+
+fn default_term_template() -> String {
+    "term.html".to_owned()
+}
+
+fn is_default_term_template(template: &str) -> bool {
+    template == default_term_template()
+}
+
+/// Pages listed by home page, every page if nothing set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct HomeConfig {
+    /// Number of most recent pages listed.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) count: Option<usize>,
+
+    /// Only list pages under this section, such as `blog`.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) section: Option<String>,
+
+    /// Only list pages tagged so.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) tag: Option<String>,
+}
+
+impl HomeConfig {
+    fn is_default(&self) -> bool {
+        *self == HomeConfig::default()
+    }
+}
+
+/// Formats and size of every feed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct FeedConfig {
+    /// Publish `atom.xml`.
+    #[serde(default = "default_true")]
+    pub(crate) atom: bool,
+
+    /// Publish `rss.xml`.
+    #[serde(default = "default_true")]
+    pub(crate) rss: bool,
+
+    /// Number of most recent pages in every feed, every page if missing.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) limit: Option<usize>,
+}
+
+impl Default for FeedConfig {
+    fn default() -> Self {
+        Self {
+            atom: true,
+            rss: true,
+            limit: None,
+        }
+    }
+}
+
+impl FeedConfig {
+    fn is_default(&self) -> bool {
+        *self == FeedConfig::default()
+    }
+}
+
+/// Rules of `robots.txt`, which refers crawlers to sitemap as well.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct RobotsConfig {
+    /// Publish `robots.txt`.
+    #[serde(default = "default_true")]
+    pub(crate) enabled: bool,
+
+    #[serde(default = "default_user_agent")]
+    pub(crate) user_agent: String,
+
+    /// Paths crawlers may visit, even inside disallowed ones.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) allow: Vec<String>,
+
+    /// Paths crawlers should stay out of.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) disallow: Vec<String>,
+}
+
+impl Default for RobotsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            user_agent: default_user_agent(),
+            allow: Vec::new(),
+            disallow: Vec::new(),
+        }
+    }
+}
+
+impl RobotsConfig {
+    fn is_default(&self) -> bool {
+        *self == RobotsConfig::default()
+    }
+}
+
+fn default_user_agent() -> String {
+    "*".to_owned()
+}
+
+/// Sections indexed for search, which is off unless enabled.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SearchConfig {
+    /// Publish `search_index.json` along with `search.js` querying it.
+    #[serde(default)]
+    pub(crate) enabled: bool,
+
+    /// Only index pages under these sections, every section if empty.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) sections: Vec<String>,
+
+    /// Leave out pages under these sections.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub(crate) exclude: Vec<String>,
+}
+
+impl SearchConfig {
+    fn is_default(&self) -> bool {
+        *self == SearchConfig::default()
+    }
+
+    /// Whether pages of `section` are indexed.
+    pub(crate) fn includes(&self, section: &str) -> bool {
+        let within = |it: &String| Section::is_within(section, it.trim_matches('/'));
+
+        (self.sections.is_empty() || self.sections.iter().any(within))
+            && !self.exclude.iter().any(within)
+    }
+}
+
+/// How highlighted code is colored.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum HighlightStyle {
+    /// Colors written into `style` of every token.
+    #[default]
+    Inline,
+
+    /// Tokens carry classes, colored by generated `highlight.css`.
+    Class,
+}
+
+/// Highlighting of fenced code blocks at build time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct HighlightConfig {
+    #[serde(default = "default_true")]
+    pub(crate) enabled: bool,
+
+    #[serde(default)]
+    pub(crate) style: HighlightStyle,
+
+    /// Name of a theme bundled with syntect, such as `base16-ocean.dark`.
+    #[serde(default = "default_highlight_theme")]
+    pub(crate) theme: String,
+
+    /// Number every line of every block, which `linenos` turns on for a single one.
+    #[serde(default)]
+    pub(crate) line_numbers: bool,
+}
+
+impl Default for HighlightConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            style: HighlightStyle::default(),
+            theme: default_highlight_theme(),
+            line_numbers: false,
+        }
+    }
+}
+
+impl HighlightConfig {
+    fn is_default(&self) -> bool {
+        *self == HighlightConfig::default()
+    }
+}
+
+fn default_highlight_theme() -> String {
+    "InspiredGitHub".to_owned()
+}
+
+/// Extensions of Markdown, every one off unless turned on.
+///
+/// Fields left out fall back to the manifest for entries, and to off for the manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct MarkdownOptions {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) table: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) footnotes: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) strikethrough: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) tasklist: Option<bool>,
+
+    /// Links bare URLs and email addresses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) autolink: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) superscript: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) description_lists: Option<bool>,
+
+    /// Delimiter of front matter left in content, such as `---`, skipped when rendering.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) front_matter_delimiter: Option<String>,
+
+    /// Curly quotes, en and em dashes and ellipses out of plain punctuation.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) smart: Option<bool>,
+
+    /// Keep raw HTML and dangerous links as written, instead of leaving them out.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) unsafe_html: Option<bool>,
+}
+
+impl MarkdownOptions {
+    pub(crate) fn is_default(&self) -> bool {
+        *self == MarkdownOptions::default()
+    }
+
+    /// Options of `self` overridden by every field set in `overrides`.
+    pub(crate) fn merge(&self, overrides: &MarkdownOptions) -> MarkdownOptions {
+        MarkdownOptions {
+            table: overrides.table.or(self.table),
+            footnotes: overrides.footnotes.or(self.footnotes),
+            strikethrough: overrides.strikethrough.or(self.strikethrough),
+            tasklist: overrides.tasklist.or(self.tasklist),
+            autolink: overrides.autolink.or(self.autolink),
+            superscript: overrides.superscript.or(self.superscript),
+            description_lists: overrides.description_lists.or(self.description_lists),
+            front_matter_delimiter: overrides
+                .front_matter_delimiter
+                .clone()
+                .or_else(|| self.front_matter_delimiter.clone()),
+            smart: overrides.smart.or(self.smart),
+            unsafe_html: overrides.unsafe_html.or(self.unsafe_html),
+        }
+    }
+}
+
+/// Table of contents of pages, built from headings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct TocConfig {
+    /// Deepest level of headings listed, such as `3` for `h1` to `h3`.
+    #[serde(default = "default_max_depth")]
+    pub(crate) max_depth: u8,
+
+    /// Put a link to itself next to every heading.
+    #[serde(default)]
+    pub(crate) anchors: bool,
+}
+
+impl Default for TocConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: default_max_depth(),
+            anchors: false,
+        }
+    }
+}
+
+impl TocConfig {
+    fn is_default(&self) -> bool {
+        *self == TocConfig::default()
+    }
+}
+
+fn default_max_depth() -> u8 {
+    3
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
 impl TryFrom<String> for Manifest {
     type Error = super::error::Error;
 
@@ -31,3 +454,19 @@ impl TryFrom<String> for Manifest {
         Ok(toml::from_str(&value)?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_taxonomy_unknown_field() {
+        let manifest = "site = \"Moxide\"\n[[taxonomies]]\nname = \"tags\"\nfeed = true\n";
+        let manifest: Manifest = manifest.to_owned().try_into().unwrap();
+        assert!(manifest.taxonomies[0].feed);
+
+        let misplaced = "site = \"Moxide\"\n[[taxonomies]]\nname = \"tags\"\nbase_url = \"/\"\n";
+        let result = Manifest::try_from(misplaced.to_owned());
+        assert!(result.is_err());
+    }
+}
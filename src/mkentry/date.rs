@@ -0,0 +1,93 @@
+use std::fmt;
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone};
+use serde::{
+    de::{self, MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+/// Formats of date without offset, read as local time.
+const NAIVE_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M",
+];
+
+/// Deserialize date leniently, as written by different static site generators.
+///
+/// Accepts RFC 3339 and similar strings, dates without offset or time, and native TOML datetimes.
+pub(super) fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<FixedOffset>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(DateVisitor)
+}
+
+/// Deserialize optional date, see [`deserialize`].
+pub(super) fn deserialize_option<'de, D>(
+    deserializer: D,
+) -> Result<Option<DateTime<FixedOffset>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct Date(#[serde(deserialize_with = "deserialize")] DateTime<FixedOffset>);
+
+    Ok(Option::<Date>::deserialize(deserializer)?.map(|Date(it)| it))
+}
+
+/// Parse `input` as date, `None` if matching no known format.
+///
+/// Offset is kept as written, so the calendar date stays the same on every machine.
+fn parse(input: &str) -> Option<DateTime<FixedOffset>> {
+    let input = input.trim();
+
+    if let Ok(date) = input.parse::<DateTime<FixedOffset>>() {
+        return Some(date);
+    }
+
+    let naive = NAIVE_FORMATS
+        .iter()
+        .find_map(|it| NaiveDateTime::parse_from_str(input, it).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(input, "%Y-%m-%d")
+                .ok()?
+                .and_hms_opt(0, 0, 0)
+        })?;
+
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|it| it.fixed_offset())
+}
+
+struct DateVisitor;
+
+impl<'de> Visitor<'de> for DateVisitor {
+    type Value = DateTime<FixedOffset>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(
+            "a date like `2024-01-31`, `2024-01-31 08:00` or `2024-01-31T08:00:00+08:00`",
+        )
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        parse(v).ok_or_else(|| E::invalid_value(de::Unexpected::Str(v), &self))
+    }
+
+    /// Native TOML datetime arrives as a single entry map.
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        match map.next_entry::<String, String>()? {
+            Some((_, v)) => self.visit_str(&v),
+            None => Err(de::Error::invalid_length(0, &self)),
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use std::ops::Range;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::{Diagnostic, Error, Result};
+
+/// Format of front matter, detected by its opening delimiter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum Format {
+    /// Delimited by `+++` lines.
+    #[default]
+    Toml,
+
+    /// Delimited by `---` lines.
+    Yaml,
+
+    /// A JSON object, its braces act as delimiters.
+    Json,
+}
+
+impl Format {
+    fn detect(line: &str) -> Option<Format> {
+        match line.trim_end() {
+            "+++" => Some(Format::Toml),
+            "---" => Some(Format::Yaml),
+            line if line.starts_with('{') => Some(Format::Json),
+            _ => None,
+        }
+    }
+
+    fn delimiter(&self) -> &'static str {
+        match self {
+            Format::Toml => "+++",
+            Format::Yaml => "---",
+            Format::Json => "{",
+        }
+    }
+
+    /// Serialize `meta` as front matter block, delimiters included.
+    pub(crate) fn write<T>(&self, meta: &T) -> Result<String>
+    where
+        T: Serialize,
+    {
+        Ok(match self {
+            Format::Toml => format!("+++\n{}\n+++", toml::to_string_pretty(meta)?.trim()),
+            Format::Yaml => format!("---\n{}\n---", serde_yaml_ng::to_string(meta)?.trim()),
+            Format::Json => serde_json::to_string_pretty(meta)?,
+        })
+    }
+}
+
+/// Front matter block split from the very start of document.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct FrontMatter<'a> {
+    pub(crate) format: Format,
+
+    /// Content between the delimiter lines, or the whole object for JSON.
+    pub(crate) raw: &'a str,
+
+    /// Byte offset of `raw` inside document.
+    pub(crate) offset: usize,
+
+    /// Everything after the closing delimiter line, byte-exact.
+    pub(crate) body: &'a str,
+}
+
+impl FrontMatter<'_> {
+    /// Deserialize front matter, locating errors inside the whole `document`.
+    pub(crate) fn parse<T>(&self, document: &str) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let whole = self.offset..self.offset + self.raw.len();
+
+        match self.format {
+            Format::Toml => toml::from_str(self.raw).map_err(|e| {
+                Error::InvalidMeta(Box::new(Diagnostic::from_toml(document, self.offset, &e)))
+            }),
+            Format::Yaml => serde_yaml_ng::from_str(self.raw).map_err(|e| {
+                let span = e
+                    .location()
+                    .map(|it| char_span(document, self.offset + it.index()))
+                    .unwrap_or(whole);
+                let message = without_location(&e.to_string());
+                Error::InvalidMeta(Box::new(Diagnostic::new(
+                    document, span, &message, &message,
+                )))
+            }),
+            Format::Json => serde_json::from_str(self.raw).map_err(|e| {
+                let span = json_span(self.raw, &e)
+                    .map(|it| self.offset + it.start..self.offset + it.end)
+                    .unwrap_or(whole);
+                let message = without_location(&e.to_string());
+                Error::InvalidMeta(Box::new(Diagnostic::new(
+                    document, span, &message, &message,
+                )))
+            }),
+        }
+    }
+}
+
+/// Split front matter from `document`, detecting its format by the opening delimiter.
+///
+/// Only blank lines may precede the opening delimiter, and delimiters must sit on
+/// their own lines, so delimiters appearing later in the body are left alone.
+pub(crate) fn split(document: &str) -> Result<FrontMatter<'_>> {
+    let mut lines = Lines::new(document);
+
+    let (format, opening) = lines
+        .find(|(_, line)| !line.trim().is_empty())
+        .and_then(|(start, line)| Some((Format::detect(line)?, start)))
+        .ok_or_else(|| {
+            let start = document.len() - document.trim_start().len();
+            let end = document[start..]
+                .find('\n')
+                .map(|it| it + start)
+                .unwrap_or(document.len());
+
+            Error::InvalidDataBlock(Box::new(Diagnostic::new(
+                document,
+                start..end,
+                "missing front matter",
+                "document should start with `+++`, `---` or a JSON object",
+            )))
+        })?;
+
+    if format == Format::Json {
+        return split_json(document, opening);
+    }
+
+    let unterminated = || {
+        let delimiter = format.delimiter();
+        Error::InvalidDataBlock(Box::new(Diagnostic::new(
+            document,
+            opening..opening + delimiter.len(),
+            "unterminated front matter",
+            format!("front matter opened here is never closed by `{delimiter}`"),
+        )))
+    };
+
+    let offset = lines.position;
+    let (closing, _) = lines
+        .find(|(_, line)| Format::detect(line) == Some(format))
+        .ok_or_else(unterminated)?;
+
+    Ok(FrontMatter {
+        format,
+        raw: &document[offset..closing],
+        offset,
+        body: &document[lines.position..],
+    })
+}
+
+/// JSON object has no closing delimiter line, it ends where the object ends.
+fn split_json(document: &str, opening: usize) -> Result<FrontMatter<'_>> {
+    let rest = &document[opening..];
+
+    let mut stream = serde_json::Deserializer::from_str(rest).into_iter::<serde::de::IgnoredAny>();
+    if let Some(Err(e)) = stream.next() {
+        let span = json_span(rest, &e)
+            .map(|it| opening + it.start..opening + it.end)
+            .unwrap_or(opening..opening + 1);
+        let message = e.to_string();
+
+        return Err(Error::InvalidDataBlock(Box::new(Diagnostic::new(
+            document,
+            span,
+            "unterminated front matter",
+            message,
+        ))));
+    }
+
+    let end = opening + stream.byte_offset();
+
+    // Remainder of the closing line belongs to front matter too.
+    let mut lines = Lines {
+        document,
+        position: end,
+    };
+    lines.next();
+
+    Ok(FrontMatter {
+        format: Format::Json,
+        raw: &document[opening..end],
+        offset: opening,
+        body: &document[lines.position..],
+    })
+}
+
+/// Byte span of JSON error inside `source`.
+fn json_span(source: &str, e: &serde_json::Error) -> Option<Range<usize>> {
+    let line_start = source
+        .split_inclusive('\n')
+        .take(e.line().checked_sub(1)?)
+        .map(str::len)
+        .sum::<usize>();
+    let start = (line_start + e.column().saturating_sub(1)).min(source.len());
+
+    Some(char_span(source, start))
+}
+
+/// Span of the whole character starting at byte `start` of `source`.
+fn char_span(source: &str, start: usize) -> Range<usize> {
+    let len = source
+        .get(start..)
+        .and_then(|it| it.chars().next())
+        .map_or(0, char::len_utf8);
+
+    start..start + len
+}
+
+/// Drop trailing location from error message, it is relative to front matter rather than
+/// document, and diagnostic already points there.
+fn without_location(message: &str) -> String {
+    message
+        .rsplit_once(" at line ")
+        .map(|(message, _)| message)
+        .unwrap_or(message)
+        .to_owned()
+}
+
+/// Lines of document along with their byte offset, without line endings.
+struct Lines<'a> {
+    document: &'a str,
+    position: usize,
+}
+
+impl<'a> Lines<'a> {
+    fn new(document: &'a str) -> Self {
+        // Byte order mark is not part of content.
+        let position = if document.starts_with('\u{feff}') {
+            '\u{feff}'.len_utf8()
+        } else {
+            0
+        };
+
+        Lines { document, position }
+    }
+}
+
+impl<'a> Iterator for Lines<'a> {
+    type Item = (usize, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position >= self.document.len() {
+            return None;
+        }
+
+        let start = self.position;
+        let rest = &self.document[start..];
+        let (line, consumed) = match rest.find('\n') {
+            Some(end) => (&rest[..end], end + 1),
+            None => (rest, rest.len()),
+        };
+        self.position += consumed;
+
+        Some((start, line.strip_suffix('\r').unwrap_or(line)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[test]
+    fn test_split() {
+        let document =
+            "\n+++\ntitle = \"test\"\n+++\n\nbody\n\n```\n+++\nnot = \"meta\"\n+++\n```\n";
+        let front_matter = split(document).unwrap();
+
+        assert_eq!(front_matter.format, Format::Toml);
+        assert_eq!(front_matter.raw, "title = \"test\"\n");
+        assert_eq!(&document[front_matter.offset..][..5], "title");
+        assert_eq!(
+            front_matter.body,
+            "\nbody\n\n```\n+++\nnot = \"meta\"\n+++\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_split_yaml_and_json() {
+        let front_matter = split("---\ntitle: test\n---\nbody\n---\n").unwrap();
+        assert_eq!(front_matter.format, Format::Yaml);
+        assert_eq!(front_matter.raw, "title: test\n");
+        assert_eq!(front_matter.body, "body\n---\n");
+
+        let front_matter = split("{\n  \"title\": \"{test}\"\n}\nbody {}\n").unwrap();
+        assert_eq!(front_matter.format, Format::Json);
+        assert_eq!(front_matter.raw, "{\n  \"title\": \"{test}\"\n}");
+        assert_eq!(front_matter.body, "body {}\n");
+    }
+
+    #[test]
+    fn test_split_invalid() {
+        assert!(matches!(
+            split("body\n+++\ntitle = \"test\"\n+++\n"),
+            Err(Error::InvalidDataBlock(_))
+        ));
+        assert!(matches!(
+            split("+++\ntitle = \"test\"\n"),
+            Err(Error::InvalidDataBlock(_))
+        ));
+        assert!(matches!(
+            split("+++ title = \"test\" +++\n"),
+            Err(Error::InvalidDataBlock(_))
+        ));
+        assert!(matches!(
+            split("---\ntitle: test\n+++\n"),
+            Err(Error::InvalidDataBlock(_))
+        ));
+        assert!(matches!(
+            split("{\n  \"title\": \"test\"\n"),
+            Err(Error::InvalidDataBlock(_))
+        ));
+
+        // Spans must not end inside multi-byte characters.
+        let e = split("{ \"title\": 你好 }\n").unwrap_err();
+        assert!(e.snippet(false).unwrap().contains("你好"));
+
+        let document = "---\ndate: 你好\n---\n";
+        let e = split(document)
+            .unwrap()
+            .parse::<BTreeMap<String, u32>>(document)
+            .unwrap_err();
+        assert!(e.snippet(false).unwrap().contains("你好"));
+    }
+}
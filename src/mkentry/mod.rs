@@ -1,29 +1,100 @@
 use std::{
-    collections::HashSet,
+    collections::{BTreeMap, HashSet},
     fs,
-    path::{Path, PathBuf},
-    sync::OnceLock,
+    path::PathBuf,
+    sync::{Arc, OnceLock},
 };
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, FixedOffset};
 use serde::{Deserialize, Serialize};
 
-use crate::error::{Error, Result};
+use crate::{
+    error::{Error, Result},
+    manifest::MarkdownOptions,
+    render::Markdown,
+    site::Site,
+    theme::Theme,
+    vfs::Vfs,
+};
+
+mod date;
+mod front_matter;
+mod publish;
+mod section;
+
+pub(crate) use front_matter::Format;
+pub(crate) use publish::{Publishing, Status};
+pub(crate) use section::{SectionIndex, SortBy, SECTION_INDEX};
+
+/// Marker ending description of entry, inside its content.
+const MORE: &str = "<!-- more -->";
 
 #[derive(Debug, Deserialize, Default, Serialize)]
 pub(crate) struct MarkdownMeta {
     pub(crate) title: String,
-    pub(crate) date: DateTime<Local>,
+
+    #[serde(deserialize_with = "date::deserialize")]
+    pub(crate) date: DateTime<FixedOffset>,
+
+    /// Date of last notable change, `date` if missing.
+    #[serde(default, deserialize_with = "date::deserialize_option")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) updated: Option<DateTime<FixedOffset>>,
+
+    /// Drafts are left out of build unless asked for.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) draft: bool,
+
+    /// Entry is left out of build since this date.
+    #[serde(default, deserialize_with = "date::deserialize_option")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) expires: Option<DateTime<FixedOffset>>,
 
     #[serde(default = "HashSet::default")]
     #[serde(skip_serializing_if = "HashSet::is_empty")]
     pub(crate) tag: HashSet<String>,
 
+    /// Terms of taxonomies declared in manifest, keyed by taxonomy name.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) taxonomies: BTreeMap<String, Vec<String>>,
+
+    /// Last segment of permalink, made from title if missing.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) slug: Option<String>,
+
+    /// Extensions of Markdown overriding those of manifest for this entry.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "MarkdownOptions::is_default")]
+    pub(crate) markdown: MarkdownOptions,
+
+    /// Table of contents of page, shown unless `false`.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) toc: Option<bool>,
+
+    /// Output path overriding permalink pattern entirely, such as `/about/`.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) path: Option<String>,
+
     #[serde(default = "default_renderer")]
     #[serde(skip_serializing_if = "String::is_empty")]
     pub(crate) renderer: String,
 }
 
+impl MarkdownMeta {
+    /// Terms of `taxonomy`, as written.
+    pub(crate) fn terms<'a>(&'a self, taxonomy: &'a str) -> impl Iterator<Item = &'a str> {
+        let tags = self.tag.iter().filter(move |_| taxonomy == "tags");
+
+        tags.chain(self.taxonomies.get(taxonomy).into_iter().flatten())
+            .map(String::as_str)
+    }
+}
+
 fn default_renderer() -> String {
     "page".to_owned()
 }
@@ -31,34 +102,35 @@ fn default_renderer() -> String {
 pub(crate) struct MarkdownEntryContext {
     pub(crate) index: PathBuf,
 
+    /// Output directory, relative to the root of `fs`.
     pub(crate) output: PathBuf,
 
     pub(crate) entry: MarkdownEntry,
-}
 
-impl MarkdownEntryContext {
-    pub(crate) fn try_new<P>(index: P, output: P) -> Result<MarkdownEntryContext>
-    where
-        P: AsRef<Path>,
-    {
-        Ok(MarkdownEntryContext {
-            index: index.as_ref().to_path_buf(),
-            output: output.as_ref().to_path_buf(),
-            entry: MarkdownEntry::try_from(index.as_ref().to_path_buf())?,
-        })
-    }
+    pub(crate) theme: Arc<Theme>,
+
+    pub(crate) markdown: Arc<Markdown>,
+
+    pub(crate) fs: Arc<dyn Vfs>,
+
+    /// Every entry of the project, for listing and linking other pages.
+    pub(crate) site: Arc<Site>,
+
+    /// Content rendered as HTML, handed back for feeds once rendered.
+    pub(crate) content: Arc<OnceLock<String>>,
 }
 
 pub(crate) struct MarkdownEntry {
     pub(crate) meta: MarkdownMeta,
 
+    /// Format of front matter, kept for writing the document back.
+    pub(crate) format: Format,
+
     pub(crate) description: String,
 
     pub(crate) content: String,
 }
 
-static REGEX: OnceLock<regex::Regex> = OnceLock::new();
-
 impl MarkdownEntry {
     pub(crate) fn new<T>(meta: MarkdownMeta, description: T) -> Self
     where
@@ -66,67 +138,48 @@ impl MarkdownEntry {
     {
         Self {
             meta,
+            format: Format::default(),
             description: description.into(),
             content: "".into(),
         }
     }
 
     pub(crate) fn with_content(content: &str) -> Result<MarkdownEntry> {
-        let document = content.trim();
-
-        if !document.starts_with("+++") {
-            Err(Error::InvalidDataBlock)
-        } else {
-            let meta = Self::extract_meta(content)?;
-
-            let document = Self::remove_meta(content);
-
-            let description = document
-                .split_once("<!-- more -->")
-                .map(|(a, _)| a.trim())
-                .unwrap_or(&document)
-                .into();
-
-            Ok(MarkdownEntry {
-                meta,
-                content: document,
-                description,
-            })
-        }
-    }
+        let front_matter = front_matter::split(content)?;
+        let meta = front_matter.parse(content)?;
 
-    pub(crate) fn into_document(self) -> Result<String> {
-        let meta = toml::to_string_pretty(&self.meta)?.trim().to_owned();
-        let description = format!("{}\n<!-- more -->", self.description);
+        let document = front_matter.body;
+        let description = document
+            .split_once(MORE)
+            .map(|(a, _)| a.trim())
+            .unwrap_or(document)
+            .into();
 
-        let result = [
-            format!(
-                "+++
-{meta}
-+++"
-            ),
+        Ok(MarkdownEntry {
+            meta,
+            format: front_matter.format,
+            content: document.to_owned(),
             description,
-            self.content,
-        ]
-        .join("\n\n");
-
-        Ok(result)
+        })
     }
 
-    fn remove_meta(content: &str) -> String {
-        let re = REGEX.get_or_init(|| regex::Regex::new(r"(?s)\+\+\+(.*?)\+\+\+").unwrap());
-        re.replace(content, "").to_string()
+    /// Content to render, without the marker ending description.
+    pub(crate) fn body(&self) -> String {
+        self.content.replacen(MORE, "", 1)
     }
 
-    fn extract_meta(content: &str) -> Result<MarkdownMeta> {
-        let re = REGEX.get_or_init(|| regex::Regex::new(r"(?s)\+\+\+(.*?)\+\+\+").unwrap());
+    pub(crate) fn into_document(self) -> Result<String> {
+        let meta = self.format.write(&self.meta)?;
+
+        if !self.content.is_empty() {
+            // Parsed content already holds description, keep it as it was.
+            return Ok(format!("{meta}\n{}", self.content));
+        }
 
-        let meta = re
-            .captures(content)
-            .map(|caps| caps[1].trim().to_string())
-            .ok_or(Error::InvalidDataBlock)?;
+        let description = format!("{}\n{MORE}", self.description);
+        let result = [meta, description, self.content].join("\n\n");
 
-        Ok(toml::from_str(&meta)?)
+        Ok(result)
     }
 }
 
@@ -144,12 +197,14 @@ impl TryFrom<&PathBuf> for MarkdownEntry {
     fn try_from(value: &PathBuf) -> std::result::Result<Self, Self::Error> {
         let content = fs::read_to_string(value)?;
 
-        MarkdownEntry::with_content(&content)
+        MarkdownEntry::with_content(&content).map_err(|e| e.at(value))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use chrono::Local;
+
     use super::*;
 
     #[test]
@@ -157,7 +212,7 @@ mod tests {
         let binding = toml::to_string_pretty(&MarkdownMeta {
             title: "test".to_owned(),
             renderer: "page".to_owned(),
-            date: Local::now(),
+            date: Local::now().fixed_offset(),
             ..MarkdownMeta::default()
         })
         .unwrap();
@@ -180,11 +235,24 @@ test1
 "
         );
 
-        assert_eq!(
-            MarkdownEntry::with_content(&test_input)
-                .unwrap()
-                .description,
-            "test1"
-        )
+        let entry = MarkdownEntry::with_content(&test_input).unwrap();
+        assert_eq!(entry.description, "test1");
+        assert!(!entry.body().contains(MORE));
+    }
+
+    #[test]
+    fn test_meta_error_position() {
+        let test_input = "+++
+title = \"test\"
+date = \"yesterday\"
++++
+
+test1
+";
+
+        match MarkdownEntry::with_content(test_input) {
+            Err(Error::InvalidMeta(diagnostic)) => assert_eq!(diagnostic.position(), (3, 8)),
+            _ => panic!("expected invalid meta"),
+        }
     }
 }
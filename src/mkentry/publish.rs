@@ -0,0 +1,94 @@
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+use super::MarkdownMeta;
+
+/// Publication state of entry at a moment.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Status {
+    #[default]
+    Published,
+
+    /// Marked as `draft`, never published until unmarked.
+    Draft,
+
+    /// Dated in the future, published once the date comes.
+    Scheduled,
+
+    /// Past its `expires` date.
+    Expired,
+}
+
+impl MarkdownMeta {
+    pub(crate) fn status_at(&self, now: DateTime<Local>) -> Status {
+        if self.draft {
+            Status::Draft
+        } else if self.date > now {
+            Status::Scheduled
+        } else if self.expires.is_some_and(|it| it <= now) {
+            Status::Expired
+        } else {
+            Status::Published
+        }
+    }
+}
+
+/// Which unpublished entries are built anyway, only published ones by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Publishing {
+    pub(crate) drafts: bool,
+    pub(crate) future: bool,
+    pub(crate) expired: bool,
+}
+
+impl Publishing {
+    /// Build every entry regardless of its state, as previewing does.
+    pub(crate) fn all() -> Self {
+        Self {
+            drafts: true,
+            future: true,
+            expired: true,
+        }
+    }
+
+    pub(crate) fn includes(&self, meta: &MarkdownMeta, now: DateTime<Local>) -> bool {
+        (self.drafts || !meta.draft)
+            && (self.future || meta.date <= now)
+            && (self.expired || meta.expires.is_none_or(|it| it > now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_publishing() {
+        let now = Local::now();
+        let scheduled_draft = MarkdownMeta {
+            draft: true,
+            date: (now + Duration::days(1)).fixed_offset(),
+            ..MarkdownMeta::default()
+        };
+        let expired = MarkdownMeta {
+            date: (now - Duration::days(2)).fixed_offset(),
+            expires: Some((now - Duration::days(1)).fixed_offset()),
+            ..MarkdownMeta::default()
+        };
+
+        assert_eq!(scheduled_draft.status_at(now), Status::Draft);
+        assert_eq!(expired.status_at(now), Status::Expired);
+
+        let drafts = Publishing {
+            drafts: true,
+            ..Publishing::default()
+        };
+        assert!(!drafts.includes(&scheduled_draft, now));
+        assert!(!drafts.includes(&expired, now));
+        assert!(Publishing::all().includes(&scheduled_draft, now));
+        assert!(Publishing::all().includes(&expired, now));
+    }
+}
@@ -0,0 +1,74 @@
+use std::{fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+use super::front_matter;
+
+/// File giving a section its title and settings, sitting in the section directory.
+pub(crate) const SECTION_INDEX: &str = "_index.md";
+
+/// How pages of a section are ordered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SortBy {
+    /// Newest first.
+    #[default]
+    Date,
+
+    /// Alphabetically.
+    Title,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct SectionMeta {
+    /// Title of section, directory name if missing.
+    #[serde(default)]
+    pub(crate) title: Option<String>,
+
+    #[serde(default)]
+    pub(crate) sort_by: SortBy,
+
+    /// Reverse order given by `sort_by`.
+    #[serde(default)]
+    pub(crate) reverse: bool,
+
+    /// Pages listed on every page of section list.
+    #[serde(default)]
+    pub(crate) paginate_by: Option<usize>,
+
+    /// Publish a feed of pages under section.
+    #[serde(default)]
+    pub(crate) feed: bool,
+}
+
+/// Parsed `_index.md` of section.
+#[derive(Debug, Default)]
+pub(crate) struct SectionIndex {
+    pub(crate) meta: SectionMeta,
+
+    /// Markdown shown above the list of pages.
+    pub(crate) content: String,
+}
+
+impl SectionIndex {
+    pub(crate) fn with_content(content: &str) -> Result<SectionIndex> {
+        let front_matter = front_matter::split(content)?;
+
+        Ok(SectionIndex {
+            meta: front_matter.parse(content)?,
+            content: front_matter.body.to_owned(),
+        })
+    }
+}
+
+impl TryFrom<&PathBuf> for SectionIndex {
+    type Error = Error;
+
+    fn try_from(value: &PathBuf) -> std::result::Result<Self, Self::Error> {
+        let content = fs::read_to_string(value)?;
+
+        SectionIndex::with_content(&content).map_err(|e| e.at(value))
+    }
+}
@@ -0,0 +1,214 @@
+use std::path::{Component, Path, PathBuf};
+
+use chrono::Datelike;
+use itertools::Itertools;
+
+use crate::{
+    error::{Error, Result},
+    mkentry::MarkdownMeta,
+};
+
+pub(crate) use slug::{slugify, SlugStyle};
+
+mod slug;
+
+/// Permalink pattern of entries, such as `/:year/:month/:slug/`.
+///
+/// Every permalink is a directory, rendered entry lives in its `index.html`.
+#[derive(Debug, Clone)]
+pub(crate) struct Permalink {
+    segments: Vec<Segment>,
+    style: SlugStyle,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Year,
+    Month,
+    Day,
+    Slug,
+    Section,
+}
+
+impl Permalink {
+    pub(crate) fn try_new(pattern: &str, style: SlugStyle) -> Result<Permalink> {
+        let segments = pattern
+            .split('/')
+            .filter(|it| !it.is_empty())
+            .map(|it| match it {
+                ":year" => Ok(Segment::Year),
+                ":month" => Ok(Segment::Month),
+                ":day" => Ok(Segment::Day),
+                ":slug" => Ok(Segment::Slug),
+                ":section" => Ok(Segment::Section),
+                it if it.starts_with(':') => Err(Error::InvalidPermalink(format!(
+                    "unknown placeholder `{it}` in `{pattern}`"
+                ))),
+                it if it == "." || it == ".." => Err(Error::InvalidPermalink(format!(
+                    "relative segment `{it}` in `{pattern}`"
+                ))),
+                it => Ok(Segment::Literal(it.to_owned())),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        if !segments.contains(&Segment::Slug) {
+            return Err(Error::InvalidPermalink(format!(
+                "`{pattern}` should contain `:slug` to tell entries apart"
+            )));
+        }
+
+        Ok(Permalink { segments, style })
+    }
+
+    /// Slugified path of section at `path` relative to `src`, as used in URLs.
+    pub(crate) fn section(&self, path: &str) -> String {
+        path.split('/')
+            .filter(|it| !it.is_empty())
+            .map(|it| {
+                let name = urlencoding::decode(it).unwrap_or(it.into());
+                slugify(&name, self.style)
+            })
+            .join("/")
+    }
+
+    /// Slugified path of taxonomy `name`, or of its `term` if given.
+    pub(crate) fn taxonomy(&self, name: &str, term: Option<&str>) -> String {
+        let name = slugify(name, self.style);
+
+        match term {
+            Some(term) => format!("{name}/{}", slugify(term, self.style)),
+            None => name,
+        }
+    }
+
+    /// Output directory of entry with `meta` in `section`, relative to output root.
+    ///
+    /// `path` of meta overrides the pattern, and `slug` of meta overrides the one
+    /// made from title.
+    pub(crate) fn output(&self, meta: &MarkdownMeta, section: &str) -> Result<PathBuf> {
+        if let Some(path) = &meta.path {
+            return relative(path);
+        }
+
+        let slug = meta
+            .slug
+            .clone()
+            .unwrap_or_else(|| slugify(&meta.title, self.style));
+        let date = meta.date;
+
+        let output = self
+            .segments
+            .iter()
+            .map(|it| match it {
+                Segment::Literal(literal) => literal.clone(),
+                Segment::Year => format!("{:04}", date.year()),
+                Segment::Month => format!("{:02}", date.month()),
+                Segment::Day => format!("{:02}", date.day()),
+                Segment::Slug => slug.clone(),
+                Segment::Section => self.section(section),
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+
+        relative(&output)
+    }
+}
+
+/// URL of page published at `output` directory.
+pub(crate) fn url(output: &Path) -> String {
+    let path = output
+        .components()
+        .map(|it| it.as_os_str().to_string_lossy())
+        .join("/");
+
+    if path.is_empty() {
+        "/".to_owned()
+    } else {
+        format!("/{path}/")
+    }
+}
+
+/// Turn URL path into output directory, refusing to step out of output root.
+fn relative(path: &str) -> Result<PathBuf> {
+    let output = Path::new(path.trim_start_matches('/'));
+
+    if output
+        .components()
+        .any(|it| !matches!(it, Component::Normal(_)))
+    {
+        return Err(Error::InvalidPermalink(format!(
+            "`{path}` should be a plain path"
+        )));
+    }
+
+    Ok(output.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, TimeZone};
+
+    use super::*;
+
+    #[test]
+    fn test_output() {
+        let permalink = Permalink::try_new("/posts/:year/:month/:slug/", SlugStyle::Ascii).unwrap();
+        // Date is taken as written, whatever the time zone of building machine.
+        let meta = MarkdownMeta {
+            title: "Hello, World!".to_owned(),
+            date: FixedOffset::east_opt(8 * 3600)
+                .unwrap()
+                .with_ymd_and_hms(2024, 1, 1, 0, 0, 0)
+                .unwrap(),
+            ..MarkdownMeta::default()
+        };
+
+        assert_eq!(
+            permalink.output(&meta, "").unwrap(),
+            Path::new("posts/2024/01/hello-world")
+        );
+
+        let meta = MarkdownMeta {
+            slug: Some("hi".to_owned()),
+            ..meta
+        };
+        assert_eq!(
+            permalink.output(&meta, "").unwrap(),
+            Path::new("posts/2024/01/hi")
+        );
+
+        let meta = MarkdownMeta {
+            path: Some("/about/".to_owned()),
+            ..meta
+        };
+        assert_eq!(permalink.output(&meta, "").unwrap(), Path::new("about"));
+
+        let meta = MarkdownMeta {
+            path: Some("/../etc".to_owned()),
+            ..meta
+        };
+        assert!(permalink.output(&meta, "").is_err());
+    }
+
+    #[test]
+    fn test_section() {
+        let permalink = Permalink::try_new("/:section/:slug/", SlugStyle::Ascii).unwrap();
+        let meta = MarkdownMeta {
+            title: "Hello".to_owned(),
+            ..MarkdownMeta::default()
+        };
+
+        assert_eq!(
+            permalink.output(&meta, "Blog/My%20Notes").unwrap(),
+            Path::new("blog/my-notes/hello")
+        );
+        assert_eq!(permalink.output(&meta, "").unwrap(), Path::new("hello"));
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(Permalink::try_new("/:year/:title/", SlugStyle::Ascii).is_err());
+        assert!(Permalink::try_new("/:year/", SlugStyle::Ascii).is_err());
+    }
+}
@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+/// Slug used when nothing readable is left of input.
+const FALLBACK: &str = "untitled";
+
+/// How titles are turned into slugs, set by `slug` of manifest.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SlugStyle {
+    /// Transliterate every script into ASCII, such as pinyin for Chinese.
+    #[default]
+    Ascii,
+
+    /// Keep letters of every script as they are.
+    Unicode,
+}
+
+/// Make URL friendly slug of `input`, lowercase words joined by `-`.
+pub(crate) fn slugify(input: &str, style: SlugStyle) -> String {
+    let input = match style {
+        SlugStyle::Ascii => deunicode::deunicode(input),
+        SlugStyle::Unicode => input.to_owned(),
+    };
+
+    let slug = input
+        .split(|it: char| !it.is_alphanumeric())
+        .filter(|it| !it.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-");
+
+    if slug.is_empty() {
+        FALLBACK.to_owned()
+    } else {
+        slug
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Hello, World!", SlugStyle::Ascii), "hello-world");
+        assert_eq!(slugify("你好，世界", SlugStyle::Ascii), "ni-hao-shi-jie");
+        assert_eq!(slugify("Café Ünïcode", SlugStyle::Ascii), "cafe-unicode");
+        assert_eq!(slugify("你好，世界", SlugStyle::Unicode), "你好-世界");
+        assert_eq!(slugify("!!!", SlugStyle::Ascii), "untitled");
+    }
+}
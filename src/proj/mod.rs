@@ -1,19 +1,31 @@
 use std::{
+    collections::{BTreeMap, HashMap, HashSet},
     fs,
     io::Write,
     path::{Path, PathBuf},
+    sync::Arc,
 };
 
-use chrono::Utc;
+use chrono::Local;
 use colored::Colorize;
 use itertools::Itertools;
-use log::trace;
+use log::{error, trace};
 
 use crate::{
-    error::Result,
-    manifest::Manifest,
-    mkentry::{MarkdownEntry, MarkdownEntryContext, MarkdownMeta},
-    render::RenderRegistry,
+    error::{BuildFailure, Error, Phase, Result},
+    manifest::{
+        self, FeedConfig, HighlightConfig, HomeConfig, Manifest, MarkdownOptions, RobotsConfig,
+        SearchConfig, TocConfig,
+    },
+    mkentry::{
+        MarkdownEntry, MarkdownEntryContext, MarkdownMeta, Publishing, SectionIndex, SECTION_INDEX,
+    },
+    permalink::{self, slugify, Permalink, SlugStyle},
+    render::{self, Feed, Listing, Markdown, Rendered},
+    search::SearchText,
+    site::{self, Site, SitePage},
+    theme::Theme,
+    vfs::{DiskFs, Vfs},
 };
 
 pub(crate) struct MoxideProj {
@@ -21,6 +33,8 @@ pub(crate) struct MoxideProj {
     base: PathBuf,
 
     output: Option<PathBuf>,
+
+    publishing: Publishing,
 }
 
 impl MoxideProj {
@@ -37,9 +51,14 @@ impl MoxideProj {
             manifest,
             base,
             output: None,
+            publishing: Publishing::default(),
         })
     }
 
+    pub(crate) fn path_base(&self) -> &Path {
+        &self.base
+    }
+
     pub(crate) fn path_src(&self) -> PathBuf {
         let mut src = self.base.clone();
         src.push("src");
@@ -61,22 +80,76 @@ impl MoxideProj {
         })
     }
 
+    pub(crate) fn set_publishing(&mut self, publishing: Publishing) {
+        self.publishing = publishing
+    }
+
     pub(crate) fn create_page<T>(&self, name: T) -> Result<PathBuf>
+    where
+        T: Into<String>,
+    {
+        self.create_entry(name, "page")
+    }
+
+    /// Create bundle with an `assets` directory, copying `assets` files into it.
+    ///
+    /// Every asset is checked before anything is created, so a bad one leaves no bundle
+    /// behind.
+    pub(crate) fn create_bundle<T, P>(&self, name: T, assets: &[P]) -> Result<PathBuf>
+    where
+        T: Into<String>,
+        P: AsRef<Path>,
+    {
+        let assets = assets
+            .iter()
+            .map(|asset| {
+                let asset = asset.as_ref();
+                match asset.file_name() {
+                    Some(name) if asset.is_file() => Ok((asset, name)),
+                    _ => Err(Error::InvalidAsset(asset.to_path_buf())),
+                }
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let bundle_path = self.create_entry(name, "bundle")?;
+
+        let assets_path = {
+            let mut t = bundle_path.clone();
+            t.push("assets");
+            t
+        };
+        fs::create_dir_all(&assets_path)?;
+
+        for (asset, name) in assets {
+            let target = assets_path.join(name);
+
+            fs::copy(asset, &target)?;
+            trace!(
+                "Copied asset {} into {}",
+                asset.to_str().unwrap().bold().underline(),
+                target.to_str().unwrap().bold().underline()
+            );
+        }
+
+        Ok(bundle_path)
+    }
+
+    fn create_entry<T>(&self, name: T, renderer: &str) -> Result<PathBuf>
     where
         T: Into<String>,
     {
         let name: String = name.into();
-        let encoded_name = urlencoding::encode(&name);
+        let slug = slugify(&name, self.manifest.slug);
 
         trace!(
-            "Encoded new page name {} into {}",
+            "Slugified new page name {} into {}",
             name.bold(),
-            encoded_name.bold()
+            slug.bold()
         );
 
         let page_path = {
             let mut t = self.path_src();
-            t.push(encoded_name.to_string());
+            t.push(&slug);
             t
         };
         fs::create_dir_all(&page_path)?;
@@ -95,7 +168,8 @@ impl MoxideProj {
             MarkdownEntry::new(
                 MarkdownMeta {
                     title: name,
-                    date: Utc::now().into(),
+                    date: Local::now().fixed_offset(),
+                    renderer: renderer.to_owned(),
                     ..MarkdownMeta::default()
                 },
                 "Hello,World! This is the index markdown of your `page`/`bundle`/`...`!",
@@ -116,16 +190,16 @@ impl MoxideProj {
         T: Into<String>,
     {
         let name: String = name.into();
-        let encoded_name = urlencoding::encode(&name);
+        let slug = slugify(&name, SlugStyle::default());
 
         trace!(
-            "Encoded new project name {} into {}",
+            "Slugified new project name {} into {}",
             name.bold(),
-            encoded_name.bold()
+            slug.bold()
         );
 
-        // Encoded to safe path
-        let proj = PathBuf::from_iter([encoded_name.to_string()]);
+        // Slugified to safe path
+        let proj = PathBuf::from_iter([slug]);
 
         trace!(
             "Created new Moxide project at: {}",
@@ -143,7 +217,20 @@ impl MoxideProj {
         let manifest_content = toml::to_string_pretty(&Manifest {
             site: name.clone(),
             description: "Hello,World!".to_owned(),
+            base_url: "".to_owned(),
             theme: "".to_owned(),
+            permalink: "/:year/:month/:slug/".to_owned(),
+            slug: SlugStyle::default(),
+            paginate_by: None,
+            home: HomeConfig::default(),
+            feed: FeedConfig::default(),
+            sitemap: true,
+            robots: RobotsConfig::default(),
+            search: SearchConfig::default(),
+            highlight: HighlightConfig::default(),
+            markdown: MarkdownOptions::default(),
+            toc: TocConfig::default(),
+            taxonomies: manifest::default_taxonomies(),
             renders: Vec::new(),
         })?;
         manifest_file.write_all(manifest_content.as_bytes())?;
@@ -152,6 +239,25 @@ impl MoxideProj {
     }
 }
 
+/// State of built project, kept for rebuilding it incrementally.
+#[derive(Default)]
+pub(crate) struct BuildState {
+    /// Every rendered entry, keyed by its index markdown.
+    pub(crate) rendered: HashMap<PathBuf, Rendered>,
+
+    /// Failure of every entry failed building, keyed by its index markdown.
+    pub(crate) failed: BTreeMap<PathBuf, BuildFailure>,
+
+    /// Directory of every generated list page, paginated ones included.
+    pub(crate) generated: HashSet<PathBuf>,
+
+    /// Every feed, sitemap or other file generated for the whole site.
+    pub(crate) files: HashSet<PathBuf>,
+
+    /// Site model entries were rendered with.
+    pub(crate) site: Arc<Site>,
+}
+
 impl MoxideProj {
     pub(crate) async fn build(&self) -> Result<()> {
         if fs::exists(self.path_output())? {
@@ -167,12 +273,77 @@ impl MoxideProj {
             self.path_output().to_str().unwrap().bold().underline()
         );
 
-        let output = walkdir::WalkDir::new(self.path_src())
-            .max_depth(2)
+        let built = self
+            .build_into(Arc::new(DiskFs::new(self.path_output())))
+            .await?;
+
+        if built.failed.is_empty() {
+            return Ok(());
+        }
+
+        error!("Failed building {} entries:", built.failed.len());
+        let color = colored::control::SHOULD_COLORIZE.should_colorize();
+        for (index, failure) in &built.failed {
+            error!(
+                "[{}] {}\n{}",
+                failure.phase.to_string().bold(),
+                index.to_str().unwrap().bold().underline(),
+                failure
+                    .cause
+                    .snippet(color)
+                    .unwrap_or_else(|| failure.cause.to_string())
+            );
+        }
+
+        Err(Error::BuildFailed(built.failed.len()))
+    }
+
+    /// Render every entry of project into `fs`.
+    pub(crate) async fn build_into(&self, fs: Arc<dyn Vfs>) -> Result<BuildState> {
+        trace!("Building site `{}`", self.manifest.site.bold());
+
+        let mut state = BuildState::default();
+        self.render_site(fs, &mut state, None).await?;
+
+        Ok(state)
+    }
+
+    /// Re-render entries affected by `changed` paths only, updating `state` in place.
+    ///
+    /// Outputs of removed entries are dropped from `fs`.
+    pub(crate) async fn rebuild_into(
+        &self,
+        fs: Arc<dyn Vfs>,
+        state: &mut BuildState,
+        changed: &[PathBuf],
+    ) -> Result<()> {
+        let src = self.path_src();
+
+        let affected = changed
+            .iter()
+            .flat_map(|path| {
+                path.ancestors()
+                    .take_while(|it| it.starts_with(&src))
+                    .map(|it| it.join("index.md"))
+                    .find(|it| {
+                        it.is_file()
+                            || state.rendered.contains_key(it)
+                            || state.failed.contains_key(it)
+                    })
+            })
+            .collect();
+
+        self.render_site(fs, state, Some(affected)).await
+    }
+
+    /// Walk `src` for index markdown of entries and `_index.md` of sections.
+    fn walk_sources(&self) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        walkdir::WalkDir::new(self.path_src())
+            .sort_by_file_name()
             .into_iter()
             .flatten()
             .filter(|it| it.file_type().is_file())
-            .filter(|it| it.file_name() == "index.md")
+            .filter(|it| it.file_name() == "index.md" || it.file_name() == SECTION_INDEX)
             .map(|it| it.into_path())
             .inspect(|it| {
                 trace!(
@@ -180,13 +351,114 @@ impl MoxideProj {
                     it.to_str().unwrap().bold().underline()
                 )
             })
-            .flat_map(|index| {
-                MarkdownEntryContext::try_new(&index, &index).map(|it| {
-                    let mut output = self.path_output();
-                    let date = urlencoding::encode(&it.entry.meta.date.to_string()).to_string();
-                    output.extend(["contents", &date]);
-                    MarkdownEntryContext { output, ..it }
-                })
+            .partition(|it| it.ends_with("index.md"))
+    }
+
+    /// Parse `_index.md` of sections, keyed by section path.
+    ///
+    /// Directories holding an entry aren't sections, so their `_index.md` is ignored.
+    fn parse_sections(
+        &self,
+        indexes: Vec<PathBuf>,
+        failed: &mut BTreeMap<PathBuf, BuildFailure>,
+    ) -> BTreeMap<String, SectionIndex> {
+        let src = self.path_src();
+
+        indexes
+            .into_iter()
+            .filter(|it| !it.with_file_name("index.md").is_file())
+            .flat_map(|index| match SectionIndex::try_from(&index) {
+                Ok(section) => {
+                    let dir = index.parent().unwrap_or(&src);
+                    Some((site::section_path(&src, dir), section))
+                }
+                Err(cause) => {
+                    trace!(
+                        "Failed parsing {}: {cause}",
+                        index.to_str().unwrap().bold().underline()
+                    );
+                    let phase = Phase::Parse;
+                    failed.insert(index, BuildFailure { phase, cause });
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Parse every entry to build, placing it at its permalink.
+    fn place_entries(
+        &self,
+        indexes: Vec<PathBuf>,
+        permalink: &Permalink,
+        failed: &mut BTreeMap<PathBuf, BuildFailure>,
+    ) -> Vec<Placed> {
+        let src = self.path_src();
+        let now = Local::now();
+
+        let entries = indexes
+            .into_iter()
+            .flat_map(|index| match MarkdownEntry::try_from(&index) {
+                Ok(entry) => Some((index, entry)),
+                Err(cause) => {
+                    trace!(
+                        "Failed parsing {}: {cause}",
+                        index.to_str().unwrap().bold().underline()
+                    );
+                    let phase = Phase::Parse;
+                    failed.insert(index, BuildFailure { phase, cause });
+                    None
+                }
+            })
+            .filter(|(index, entry)| {
+                let included = self.publishing.includes(&entry.meta, now);
+                if !included {
+                    trace!(
+                        "Skipping {:?} entry {}",
+                        entry.meta.status_at(now),
+                        index.to_str().unwrap().bold().underline()
+                    );
+                }
+                included
+            })
+            .collect_vec();
+
+        // Indexes are walked in order, so the same entry wins clashed permalink every build.
+        let mut claimed: HashMap<PathBuf, PathBuf> = HashMap::new();
+        entries
+            .into_iter()
+            .flat_map(|(index, entry)| {
+                let section = site::section_of(&src, &index);
+                let output =
+                    permalink.output(&entry.meta, &section).and_then(|output| {
+                        match claimed.get(&output) {
+                            Some(by) => Err(Error::PermalinkTaken {
+                                url: permalink::url(&output),
+                                by: by.clone(),
+                            }),
+                            None => Ok(output),
+                        }
+                    });
+
+                match output {
+                    Ok(output) => {
+                        claimed.insert(output.clone(), index.clone());
+                        Some(Placed {
+                            index,
+                            entry,
+                            section,
+                            output,
+                        })
+                    }
+                    Err(cause) => {
+                        trace!(
+                            "Failed resolving permalink of {}: {cause}",
+                            index.to_str().unwrap().bold().underline()
+                        );
+                        let phase = Phase::Permalink;
+                        failed.insert(index, BuildFailure { phase, cause });
+                        None
+                    }
+                }
             })
             .inspect(|it| {
                 trace!(
@@ -195,11 +467,401 @@ impl MoxideProj {
                     it.entry.meta.date.to_string().italic().bold()
                 )
             })
-            .flat_map(RenderRegistry::to_prepared_render)
-            .collect_vec();
+            .collect_vec()
+    }
+
+    /// Collect every entry into [`Site`] first, then render entries of `affected`,
+    /// or every entry if `None`, and list pages of sections.
+    ///
+    /// Any page may show others, so every entry is rendered again once the site changes.
+    async fn render_site(
+        &self,
+        fs: Arc<dyn Vfs>,
+        state: &mut BuildState,
+        affected: Option<HashSet<PathBuf>>,
+    ) -> Result<()> {
+        let theme = Arc::new(Theme::try_new(&self.base, &self.manifest)?);
+        let permalink = Permalink::try_new(&self.manifest.permalink, self.manifest.slug)?;
+        let markdown = Arc::new(Markdown::new(&self.manifest)?);
+        let mut failed = BTreeMap::new();
+
+        let (indexes, section_indexes) = self.walk_sources();
+        let sections = self.parse_sections(section_indexes, &mut failed);
+        let entries = self.place_entries(indexes, &permalink, &mut failed);
+        let site = Arc::new(self.collect_site(&permalink, &markdown, &entries, sections));
 
-        let _ = futures::future::join_all(output).await;
+        let src = self.path_src();
+        let manifest = self.base.join("manifest.toml");
+        let lists = render::lists_of(&site, &permalink, &src, &manifest);
+        let feeds = render::feeds_of(&site, &self.manifest.feed, &permalink, &src, &manifest);
+        let texts = self.search_texts(&entries);
+        let sources = self.feed_sources(&feeds, &entries);
+
+        let everything = affected.is_none() || site != state.site;
+        let selected = entries
+            .iter()
+            .filter(|it| {
+                everything
+                    || affected
+                        .as_ref()
+                        .is_some_and(|affected| affected.contains(&it.index))
+                    || state.failed.contains_key(&it.index)
+                    || state.rendered.get(&it.index).map(|it| &it.output) != Some(&it.output)
+            })
+            .map(|it| it.index.clone())
+            .collect::<HashSet<_>>();
+
+        let mut rendered = Self::drop_stale(&*fs, state, &entries, &selected, &lists).await?;
+        let contexts = entries
+            .into_iter()
+            .filter(|it| selected.contains(&it.index))
+            .map(|it| MarkdownEntryContext {
+                index: it.index,
+                output: it.output,
+                entry: it.entry,
+                theme: theme.clone(),
+                markdown: markdown.clone(),
+                fs: fs.clone(),
+                site: site.clone(),
+                content: Arc::default(),
+            })
+            .collect();
+        rendered.extend(render::render_entries(contexts, &mut failed).await);
+        state.rendered = rendered;
+
+        state.generated =
+            render::render_lists(&theme, &site, lists, &state.rendered, &*fs, &mut failed).await;
+
+        // Feeds carry content handed back by rendering pages, which site leaves out.
+        let contents = sources
+            .into_iter()
+            .map(|(index, (body, overrides))| {
+                let content = state
+                    .rendered
+                    .get(&index)
+                    .and_then(|it| it.content.clone())
+                    .unwrap_or_else(|| markdown.render_with(&body, &overrides).0);
+                (index, content)
+            })
+            .collect::<HashMap<_, _>>();
+
+        // Files of the whole site are blamed on manifest, feeds on their list.
+        let mut files = Vec::new();
+        for feed in &feeds {
+            let config = &self.manifest.feed;
+            let result = render::render_feed(&site, config, feed, &contents, &*fs).await;
+            files.push((feed.source.clone(), result));
+        }
+        if self.manifest.sitemap {
+            files.push((manifest.clone(), render::render_sitemap(&site, &*fs).await));
+        }
+        files.push((
+            manifest.clone(),
+            render::render_stylesheet(&markdown, &*fs).await,
+        ));
+        if self.manifest.search.enabled {
+            let search = &self.manifest.search;
+            let result = render::render_search(&site, search, &texts, &*fs).await;
+            files.push((manifest.clone(), result));
+        }
+        if self.manifest.robots.enabled {
+            let robots = &self.manifest.robots;
+            let result = render::render_robots(&site, robots, self.manifest.sitemap, &*fs).await;
+            files.push((manifest.clone(), result));
+        }
+
+        let mut written = HashSet::new();
+        for (index, result) in files {
+            match result {
+                Ok(files) => written.extend(files),
+                Err(cause) => render::render_failed(&mut failed, index, cause),
+            }
+        }
+
+        for path in state.files.difference(&written) {
+            trace!(
+                "Dropping stale file {}",
+                path.to_str().unwrap().bold().underline()
+            );
+            fs.remove_all(path).await?;
+        }
+        state.files = written;
+
+        state.failed = failed;
+        state.site = site;
 
         Ok(())
     }
+
+    /// Collect placed `entries` and parsed `sections` into [`Site`].
+    fn collect_site(
+        &self,
+        permalink: &Permalink,
+        markdown: &Markdown,
+        entries: &[Placed],
+        sections: BTreeMap<String, SectionIndex>,
+    ) -> Site {
+        let pages = entries
+            .iter()
+            .map(|it| {
+                SitePage::new(
+                    &self.manifest,
+                    permalink,
+                    markdown,
+                    &it.index,
+                    &it.entry,
+                    &it.output,
+                    it.section.clone(),
+                )
+            })
+            .collect();
+
+        Site::new(&self.manifest, permalink, markdown, pages, sections)
+    }
+
+    /// Plain text of every entry to index, keyed by its index markdown.
+    fn search_texts(&self, entries: &[Placed]) -> HashMap<PathBuf, SearchText> {
+        if !self.manifest.search.enabled {
+            return HashMap::new();
+        }
+
+        entries
+            .iter()
+            .map(|it| {
+                let text = SearchText::new(&it.entry.description, &it.entry.content);
+                (it.index.clone(), text)
+            })
+            .collect()
+    }
+
+    /// Markdown of every entry published by `feeds`, along with its options, keyed by its
+    /// index markdown. Kept for entries whose render doesn't hand back its content.
+    fn feed_sources(
+        &self,
+        feeds: &[Feed<'_>],
+        entries: &[Placed],
+    ) -> HashMap<PathBuf, (String, MarkdownOptions)> {
+        let published = feeds
+            .iter()
+            .flat_map(|it| it.latest(&self.manifest.feed))
+            .map(|it| &it.index)
+            .collect::<HashSet<_>>();
+
+        entries
+            .iter()
+            .filter(|it| published.contains(&it.index))
+            .map(|it| {
+                let source = (it.entry.body(), it.entry.meta.markdown.clone());
+                (it.index.clone(), source)
+            })
+            .collect()
+    }
+
+    /// Drop outputs of entries in `selected` or gone from `entries`, and lists gone from
+    /// `lists`, returning entries rendered before and kept as they are.
+    ///
+    /// Outputs of entries may nest, so only files written by the entry itself go.
+    async fn drop_stale(
+        fs: &dyn Vfs,
+        state: &mut BuildState,
+        entries: &[Placed],
+        selected: &HashSet<PathBuf>,
+        lists: &[(PathBuf, PathBuf, Listing<'_>)],
+    ) -> Result<HashMap<PathBuf, Rendered>> {
+        let present = entries.iter().map(|it| &it.index).collect::<HashSet<_>>();
+        let mut kept = HashMap::new();
+
+        for (index, it) in std::mem::take(&mut state.rendered) {
+            if present.contains(&index) && !selected.contains(&index) {
+                kept.insert(index, it);
+                continue;
+            }
+
+            trace!(
+                "Dropping stale output {}",
+                it.output.to_str().unwrap().bold().underline()
+            );
+            for file in &it.files {
+                fs.remove_all(file).await?;
+            }
+        }
+
+        for output in &state.generated {
+            if !lists.iter().any(|(_, it, _)| it == output) {
+                trace!(
+                    "Dropping stale list {}",
+                    output.to_str().unwrap().bold().underline()
+                );
+                fs.remove_all(&output.join("index.html")).await?;
+            }
+        }
+
+        Ok(kept)
+    }
+}
+
+/// Entry parsed and placed at its permalink, waiting for rendering.
+struct Placed {
+    index: PathBuf,
+    entry: MarkdownEntry,
+
+    /// Path of section holding entry, relative to `src`.
+    section: String,
+
+    output: PathBuf,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::vfs::{MemoryFs, Recording};
+
+    use super::*;
+
+    /// Project with a bare manifest under a temporary directory.
+    fn proj(dir: &Path) -> MoxideProj {
+        let manifest = dir.join("manifest.toml");
+        fs::write(&manifest, "site = \"Moxide\"").unwrap();
+        MoxideProj::try_new(manifest).unwrap()
+    }
+
+    #[test]
+    fn test_create_page() {
+        let dir = tempfile::tempdir().unwrap();
+        let proj = proj(dir.path());
+
+        // Dated in local time, so permalinks fall on the day author sees.
+        let page = proj.create_page("Hello").unwrap();
+        let entry = MarkdownEntry::try_from(page.join("index.md")).unwrap();
+        assert_eq!(entry.meta.date.offset(), Local::now().offset());
+    }
+
+    #[test]
+    fn test_create_bundle() {
+        let dir = tempfile::tempdir().unwrap();
+        let proj = proj(dir.path());
+        let figure = dir.path().join("figure.png");
+        fs::write(&figure, "png").unwrap();
+
+        for asset in [dir.path().join("missing.png"), dir.path().join("..")] {
+            let result = proj.create_bundle("Post", &[figure.clone(), asset]);
+            assert!(matches!(result, Err(Error::InvalidAsset(_))));
+            assert!(!proj.path_src().join("post").exists());
+        }
+
+        let bundle = proj.create_bundle("Post", &[figure]).unwrap();
+        assert!(bundle.join("index.md").is_file());
+        assert_eq!(fs::read(bundle.join("assets/figure.png")).unwrap(), b"png");
+    }
+
+    /// Write entry `name` under `src` of `dir`, published at `path` with `body` after its
+    /// description.
+    fn write_entry(dir: &Path, name: &str, path: &str, body: &str) -> PathBuf {
+        let index = dir.join("src").join(name).join("index.md");
+        fs::create_dir_all(index.parent().unwrap()).unwrap();
+        let meta = format!(
+            "+++\ntitle = \"{name}\"\ndate = 2024-01-01T00:00:00+08:00\npath = \"{path}\"\n+++\n"
+        );
+        fs::write(&index, format!("{meta}Summary\n<!-- more -->\n{body}")).unwrap();
+        index
+    }
+
+    #[tokio::test]
+    async fn test_build_failed() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut proj = proj(dir.path());
+        write_entry(dir.path(), "fine", "/fine/", "Fine");
+        let broken = dir.path().join("src/broken/index.md");
+        fs::create_dir_all(broken.parent().unwrap()).unwrap();
+        fs::write(&broken, "+++\ntitle = \n+++\n").unwrap();
+        let unknown = write_entry(dir.path(), "unknown", "/unknown/", "Unknown");
+        let content = fs::read_to_string(&unknown).unwrap();
+        fs::write(
+            &unknown,
+            content.replacen("+++\n", "+++\nrenderer = \"missing\"\n", 1),
+        )
+        .unwrap();
+
+        let memory = Arc::new(MemoryFs::default());
+        let state = proj.build_into(memory.clone()).await.unwrap();
+
+        let phases = state
+            .failed
+            .iter()
+            .map(|(index, it)| (index.clone(), it.phase));
+        assert_eq!(
+            phases.collect::<Vec<_>>(),
+            [(broken, Phase::Parse), (unknown, Phase::Render)]
+        );
+        assert!(memory.read(Path::new("fine/index.html")).is_some());
+        assert!(memory.read(Path::new("index.html")).is_some());
+
+        proj.set_output(dir.path().join("output"));
+        assert!(matches!(proj.build().await, Err(Error::BuildFailed(2))));
+        assert!(dir.path().join("output/fine/index.html").is_file());
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_changed() {
+        let dir = tempfile::tempdir().unwrap();
+        let proj = proj(dir.path());
+        let first = write_entry(dir.path(), "first", "/first/", "One");
+        write_entry(dir.path(), "second", "/second/", "Two");
+
+        let memory = Arc::new(MemoryFs::default());
+        let mut state = proj.build_into(memory.clone()).await.unwrap();
+        assert!(state.failed.is_empty());
+
+        write_entry(dir.path(), "first", "/first/", "Uno");
+        let recording = Arc::new(Recording::new(memory.clone()));
+        proj.rebuild_into(recording.clone(), &mut state, &[first])
+            .await
+            .unwrap();
+
+        let written = recording.written();
+        assert!(written.contains(Path::new("first/index.html")));
+        assert!(!written.contains(Path::new("second/index.html")));
+        let html = memory.read(Path::new("first/index.html")).unwrap();
+        assert!(String::from_utf8_lossy(&html).contains("Uno"));
+        assert!(memory.read(Path::new("second/index.html")).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let proj = proj(dir.path());
+        write_entry(dir.path(), "first", "/first/", "One");
+        let second = write_entry(dir.path(), "second", "/second/", "Two");
+
+        let memory = Arc::new(MemoryFs::default());
+        let mut state = proj.build_into(memory.clone()).await.unwrap();
+
+        fs::remove_dir_all(second.parent().unwrap()).unwrap();
+        proj.rebuild_into(memory.clone(), &mut state, std::slice::from_ref(&second))
+            .await
+            .unwrap();
+
+        assert!(memory.read(Path::new("second/index.html")).is_none());
+        assert!(memory.read(Path::new("first/index.html")).is_some());
+        assert!(!state.rendered.contains_key(&second));
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_nested() {
+        let dir = tempfile::tempdir().unwrap();
+        let proj = proj(dir.path());
+        let docs = write_entry(dir.path(), "docs", "/docs/", "Docs");
+        write_entry(dir.path(), "install", "/docs/install/", "Install");
+
+        let memory = Arc::new(MemoryFs::default());
+        let mut state = proj.build_into(memory.clone()).await.unwrap();
+
+        write_entry(dir.path(), "docs", "/docs/", "Read the docs");
+        proj.rebuild_into(memory.clone(), &mut state, &[docs])
+            .await
+            .unwrap();
+
+        let html = memory.read(Path::new("docs/index.html")).unwrap();
+        assert!(String::from_utf8_lossy(&html).contains("Read the docs"));
+        assert!(memory.read(Path::new("docs/install/index.html")).is_some());
+    }
 }
@@ -1,3 +0,0 @@
-pub(crate) trait Property {
-    fn merge(self, income: Self) -> Self;
-}
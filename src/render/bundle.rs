@@ -1,11 +1,23 @@
+use std::path::{Path, PathBuf};
+
 use async_trait::async_trait;
 use colored::Colorize;
-use log::info;
+use itertools::Itertools;
+use log::{info, trace};
+use walkdir::WalkDir;
 
-use crate::error::Result;
+use crate::{error::Result, mkentry::SECTION_INDEX, theme::ResourceContext};
 
 use super::{MarkdownEntryContext, Render};
 
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "svg", "webp", "avif", "bmp", "ico",
+];
+
+/// Render of page bundle, a directory holding `index.md` with its resources.
+///
+/// Every other file inside the bundle directory is published next to the rendered
+/// `index.html` with the same relative path, so relative links in Markdown keep working.
 #[derive(Debug)]
 pub(super) struct BundleRender;
 
@@ -13,11 +25,113 @@ pub(super) struct BundleRender;
 impl Render for BundleRender {
     async fn render(&self, ctx: MarkdownEntryContext) -> Result<()> {
         info!(
-            "Rendering content `{}` to `{}`",
+            "Rendering bundle `{}` to `{}`",
             ctx.entry.meta.title.bold(),
             ctx.output.to_str().unwrap().bold().underline()
         );
 
-        Ok(())
+        let bundle = ctx.index.parent().unwrap_or(Path::new("."));
+        let mut resources = Vec::new();
+
+        for (source, relative) in Self::walk_resources(bundle, &ctx.index) {
+            let target = ctx.output.join(&relative);
+
+            trace!(
+                "Copying bundle resource {} to {}",
+                source.to_str().unwrap().underline(),
+                target.to_str().unwrap().underline()
+            );
+            let size = ctx.fs.copy(&source, &target).await?;
+
+            resources.push(ResourceContext {
+                name: relative
+                    .file_name()
+                    .map(|it| it.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                path: relative
+                    .components()
+                    .map(|it| it.as_os_str().to_string_lossy())
+                    .join("/"),
+                kind: Self::kind_of(&relative),
+                size,
+            });
+        }
+
+        super::render_page(&ctx, resources).await
+    }
+}
+
+impl BundleRender {
+    /// Collect files of bundle as `(source, path relative to bundle)`.
+    ///
+    /// Nested directories holding their own `index.md` are separate entries and skipped,
+    /// and so is index markdown of sections, which isn't published as is.
+    fn walk_resources(bundle: &Path, index: &Path) -> Vec<(PathBuf, PathBuf)> {
+        WalkDir::new(bundle)
+            .min_depth(1)
+            .sort_by_file_name()
+            .into_iter()
+            .filter_entry(|it| !(it.file_type().is_dir() && it.path().join("index.md").is_file()))
+            .flatten()
+            .filter(|it| it.file_type().is_file() && it.path() != index)
+            .filter(|it| it.file_name() != SECTION_INDEX)
+            .flat_map(|it| {
+                let relative = it.path().strip_prefix(bundle).ok()?.to_path_buf();
+                Some((it.into_path(), relative))
+            })
+            .collect_vec()
+    }
+
+    fn kind_of(path: &Path) -> &'static str {
+        let is_image = path
+            .extension()
+            .and_then(|it| it.to_str())
+            .map(|it| IMAGE_EXTENSIONS.contains(&it.to_ascii_lowercase().as_str()))
+            .unwrap_or(false);
+
+        if is_image {
+            "image"
+        } else {
+            "file"
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn test_walk_resources() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path();
+        for file in [
+            "index.md",
+            "_index.md",
+            "cover.PNG",
+            "assets/data.csv",
+            "assets/figures/plot.svg",
+            "assets/figures/_index.md",
+            "nested/index.md",
+            "nested/photo.jpg",
+        ] {
+            let path = bundle.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        let resources = BundleRender::walk_resources(bundle, &bundle.join("index.md"))
+            .into_iter()
+            .map(|(_, relative)| relative)
+            .collect_vec();
+        assert_eq!(
+            resources,
+            ["assets/data.csv", "assets/figures/plot.svg", "cover.PNG"].map(PathBuf::from)
+        );
+
+        let kinds = resources.iter().map(|it| BundleRender::kind_of(it));
+        assert_eq!(kinds.collect_vec(), ["file", "image", "image"]);
     }
 }
@@ -0,0 +1,391 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    iter,
+    path::{Path, PathBuf},
+};
+
+use chrono::Local;
+use colored::Colorize;
+use log::{info, warn};
+
+use crate::{
+    error::Result,
+    manifest::{FeedConfig, Manifest},
+    mkentry::SECTION_INDEX,
+    permalink::Permalink,
+    site::{Link, Section, Site, SitePage},
+    vfs::Vfs,
+};
+
+use super::escape_xml;
+
+/// Format of feed, each published as its own file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Atom,
+    Rss,
+}
+
+impl Format {
+    fn enabled(config: &FeedConfig) -> impl Iterator<Item = Format> {
+        [(Format::Atom, config.atom), (Format::Rss, config.rss)]
+            .into_iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(it, _)| it)
+    }
+
+    /// Name of format, which is also the subtype of its MIME type when lowercased.
+    fn name(self) -> &'static str {
+        match self {
+            Format::Atom => "Atom",
+            Format::Rss => "RSS",
+        }
+    }
+
+    fn file_name(self) -> &'static str {
+        match self {
+            Format::Atom => "atom.xml",
+            Format::Rss => "rss.xml",
+        }
+    }
+}
+
+/// Links to every enabled feed of list at `url`, none without `base_url` of `manifest`.
+pub(crate) fn feed_links(manifest: &Manifest, url: &str) -> Vec<Link> {
+    if manifest.base_url.trim_end_matches('/').is_empty() {
+        return Vec::new();
+    }
+
+    Format::enabled(&manifest.feed)
+        .map(|it| Link {
+            title: it.name().to_owned(),
+            url: format!("{url}{}", it.file_name()),
+        })
+        .collect()
+}
+
+/// Pages of a list published as feeds.
+pub(crate) struct Feed<'a> {
+    pub(crate) title: String,
+
+    /// URL of the list, feeds sit right under it.
+    pub(crate) url: &'a str,
+
+    /// Output directory of the list, relative to output root.
+    pub(crate) output: PathBuf,
+
+    /// Source blamed once feed fails.
+    pub(crate) source: PathBuf,
+
+    /// Pages of list, newest first.
+    pub(crate) pages: Vec<&'a SitePage>,
+}
+
+/// Feeds of the whole site, of sections asking for them and of terms of taxonomies
+/// asking for them.
+///
+/// Feeds of sections are blamed on their `_index.md` under `src`, feeds of terms on
+/// their newest page, and the one of site on `manifest`. None is published without
+/// `base_url` of site, since readers take links of feeds as absolute.
+pub(crate) fn feeds_of<'a>(
+    site: &'a Site,
+    config: &FeedConfig,
+    permalink: &Permalink,
+    src: &Path,
+    manifest: &Path,
+) -> Vec<Feed<'a>> {
+    if site.base_url.is_empty() {
+        if Format::enabled(config).next().is_some() {
+            warn!("Skipping feeds, `base_url` of manifest is not set.");
+        }
+        return Vec::new();
+    }
+
+    let whole = Feed {
+        title: site.title.clone(),
+        url: "/",
+        output: PathBuf::new(),
+        source: manifest.to_path_buf(),
+        pages: site.pages.iter().collect(),
+    };
+    let sections = site
+        .sections
+        .values()
+        .filter(|it| !it.path.is_empty() && !it.feeds.is_empty())
+        .map(|section| Feed {
+            title: format!("{} - {}", section.title, site.title),
+            url: &section.url,
+            output: PathBuf::from(permalink.section(&section.path)),
+            source: src.join(&section.path).join(SECTION_INDEX),
+            pages: site
+                .pages
+                .iter()
+                .filter(|it| Section::is_within(&it.section, &section.path))
+                .collect(),
+        });
+    let terms = site.taxonomies.values().flat_map(|taxonomy| {
+        taxonomy
+            .terms
+            .iter()
+            .filter(|it| !it.feeds.is_empty())
+            .map(|term| Feed {
+                title: format!("{} - {}", term.name, site.title),
+                url: &term.url,
+                output: PathBuf::from(permalink.taxonomy(&taxonomy.name, Some(&term.name))),
+                source: term.pages[0].index.clone(),
+                pages: term.pages.iter().collect(),
+            })
+    });
+
+    iter::once(whole)
+        .filter(|_| !site.feeds.is_empty())
+        .chain(sections)
+        .chain(terms)
+        .collect()
+}
+
+impl<'a> Feed<'a> {
+    /// Pages published in feed, newest first up to `limit` of `config`.
+    pub(crate) fn latest(&self, config: &FeedConfig) -> &[&'a SitePage] {
+        &self.pages[..config.limit.unwrap_or(usize::MAX).min(self.pages.len())]
+    }
+}
+
+/// Write every enabled format of `feed` under its output, returning every file written.
+///
+/// `contents` holds content of pages rendered as HTML, keyed by their index markdown.
+pub(crate) async fn render_feed(
+    site: &Site,
+    config: &FeedConfig,
+    feed: &Feed<'_>,
+    contents: &HashMap<PathBuf, String>,
+    fs: &dyn Vfs,
+) -> Result<Vec<PathBuf>> {
+    let pages = feed.latest(config);
+    let mut written = Vec::new();
+
+    for format in Format::enabled(config) {
+        let path = feed.output.join(format.file_name());
+        info!(
+            "Rendering feed `{}` to `{}`",
+            feed.title.bold(),
+            path.to_str().unwrap().bold().underline()
+        );
+
+        let xml = match format {
+            Format::Atom => atom(site, feed, pages, contents),
+            Format::Rss => rss(site, feed, pages, contents),
+        };
+        fs.write(&path, xml.into_bytes()).await?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+fn atom(
+    site: &Site,
+    feed: &Feed<'_>,
+    pages: &[&SitePage],
+    contents: &HashMap<PathBuf, String>,
+) -> String {
+    let url = format!("{}{}", site.base_url, feed.url);
+    let updated = pages
+        .iter()
+        .map(|it| it.modified())
+        .max()
+        .unwrap_or_else(|| Local::now().fixed_offset());
+
+    let mut xml = String::new();
+    xml.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    xml.push_str(r#"<feed xmlns="http://www.w3.org/2005/Atom">"#);
+    let _ = write!(
+        xml,
+        r#"<title>{}</title><subtitle>{}</subtitle><link href="{}" rel="self"/><link href="{}"/><id>{}</id><updated>{}</updated><author><name>{}</name></author>"#,
+        escape_xml(&feed.title),
+        escape_xml(&site.description),
+        escape_xml(&format!("{url}{}", Format::Atom.file_name())),
+        escape_xml(&url),
+        escape_xml(&url),
+        updated.to_rfc3339(),
+        escape_xml(&site.title),
+    );
+
+    for page in pages {
+        let url = format!("{}{}", site.base_url, page.url);
+        let _ = write!(
+            xml,
+            r#"<entry xml:base="{}"><title>{}</title><link href="{}"/><id>{}</id><published>{}</published><updated>{}</updated><summary type="html">{}</summary><content type="html">{}</content></entry>"#,
+            escape_xml(&url),
+            escape_xml(&page.title),
+            escape_xml(&url),
+            escape_xml(&url),
+            page.date.to_rfc3339(),
+            page.modified().to_rfc3339(),
+            escape_xml(&page.description),
+            escape_xml(content_of(page, contents)),
+        );
+    }
+
+    xml.push_str("</feed>");
+    xml
+}
+
+fn rss(
+    site: &Site,
+    feed: &Feed<'_>,
+    pages: &[&SitePage],
+    contents: &HashMap<PathBuf, String>,
+) -> String {
+    let url = format!("{}{}", site.base_url, feed.url);
+    let updated = pages
+        .iter()
+        .map(|it| it.modified())
+        .max()
+        .unwrap_or_else(|| Local::now().fixed_offset());
+
+    let mut xml = String::new();
+    xml.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    xml.push_str(r#"<rss version="2.0" xmlns:atom="http://www.w3.org/2005/Atom" xmlns:content="http://purl.org/rss/1.0/modules/content/"><channel>"#);
+    let _ = write!(
+        xml,
+        r#"<title>{}</title><link>{}</link><description>{}</description><atom:link href="{}" rel="self" type="application/rss+xml"/><lastBuildDate>{}</lastBuildDate>"#,
+        escape_xml(&feed.title),
+        escape_xml(&url),
+        escape_xml(&site.description),
+        escape_xml(&format!("{url}{}", Format::Rss.file_name())),
+        updated.to_rfc2822(),
+    );
+
+    for page in pages {
+        let url = format!("{}{}", site.base_url, page.url);
+        let _ = write!(
+            xml,
+            r#"<item xml:base="{}"><title>{}</title><link>{}</link><guid>{}</guid><pubDate>{}</pubDate><description>{}</description><content:encoded>{}</content:encoded></item>"#,
+            escape_xml(&url),
+            escape_xml(&page.title),
+            escape_xml(&url),
+            escape_xml(&url),
+            page.date.to_rfc2822(),
+            escape_xml(&page.description),
+            escape_xml(content_of(page, contents)),
+        );
+    }
+
+    xml.push_str("</channel></rss>");
+    xml
+}
+
+/// Rendered content of `page`, falling back to its description.
+fn content_of<'a>(page: &'a SitePage, contents: &'a HashMap<PathBuf, String>) -> &'a str {
+    contents
+        .get(&page.index)
+        .map(String::as_str)
+        .unwrap_or(&page.description)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{mkentry::SectionIndex, permalink::SlugStyle, render::Markdown};
+
+    use super::*;
+
+    #[test]
+    fn test_atom() {
+        let site = Site {
+            title: "Moxide".to_owned(),
+            base_url: "https://example.com".to_owned(),
+            ..Site::default()
+        };
+        let page = SitePage {
+            index: PathBuf::from("src/post/index.md"),
+            title: "Fish & Chips".to_owned(),
+            url: "/post/".to_owned(),
+            ..SitePage::default()
+        };
+        let feed = Feed {
+            title: site.title.clone(),
+            url: "/",
+            output: PathBuf::new(),
+            source: PathBuf::from("manifest.toml"),
+            pages: vec![&page],
+        };
+        let contents = [(page.index.clone(), "<p>Hi</p>".to_owned())].into();
+
+        let xml = atom(&site, &feed, &feed.pages, &contents);
+        assert!(xml.contains(r#"<link href="https://example.com/atom.xml" rel="self"/>"#));
+        assert!(xml.contains("<title>Fish &amp; Chips</title>"));
+        assert!(xml.contains(r#"<entry xml:base="https://example.com/post/">"#));
+        assert!(xml.contains(r#"<link href="https://example.com/post/"/>"#));
+        assert!(xml.contains(r#"<content type="html">&lt;p&gt;Hi&lt;/p&gt;</content>"#));
+
+        let xml = rss(&site, &feed, &feed.pages, &contents);
+        assert!(xml.contains(r#"<item xml:base="https://example.com/post/">"#));
+    }
+
+    #[test]
+    fn test_feed_links() {
+        let manifest: Manifest = "site = \"Moxide\"".to_owned().try_into().unwrap();
+        assert!(feed_links(&manifest, "/blog/").is_empty());
+
+        let manifest: Manifest = "site = \"Moxide\"\nbase_url = \"https://example.com\""
+            .to_owned()
+            .try_into()
+            .unwrap();
+        let links = feed_links(&manifest, "/blog/");
+        assert_eq!(links[1].url, "/blog/rss.xml");
+    }
+
+    #[test]
+    fn test_feeds_of() {
+        let manifest: Manifest = "site = \"Moxide\"\nbase_url = \"https://example.com\""
+            .to_owned()
+            .try_into()
+            .unwrap();
+        let page = SitePage {
+            index: PathBuf::from("src/blog/2024/post/index.md"),
+            section: "blog/2024".to_owned(),
+            ..SitePage::default()
+        };
+        let mut blog = Section::new(
+            "blog".to_owned(),
+            "Blog".to_owned(),
+            "/blog/".to_owned(),
+            &SectionIndex::default(),
+            &Markdown::default(),
+        );
+        blog.feeds = feed_links(&manifest, &blog.url);
+        let mut site = Site {
+            base_url: "https://example.com".to_owned(),
+            pages: vec![page],
+            sections: [("blog".to_owned(), blog)].into(),
+            ..Site::default()
+        };
+        let permalink = Permalink::try_new("/:slug/", SlugStyle::Ascii).unwrap();
+
+        // Site has no feeds of its own, only the section asks for them.
+        let feeds = feeds_of(
+            &site,
+            &manifest.feed,
+            &permalink,
+            Path::new("src"),
+            Path::new("manifest.toml"),
+        );
+        assert_eq!(feeds.len(), 1);
+        assert_eq!(feeds[0].output, Path::new("blog"));
+        assert_eq!(feeds[0].source, Path::new("src/blog/_index.md"));
+        assert_eq!(feeds[0].pages.len(), 1);
+
+        // Without base_url, links of feeds could not be absolute.
+        site.base_url.clear();
+        let feeds = feeds_of(
+            &site,
+            &manifest.feed,
+            &permalink,
+            Path::new("src"),
+            Path::new("manifest.toml"),
+        );
+        assert!(feeds.is_empty());
+    }
+}
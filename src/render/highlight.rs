@@ -0,0 +1,367 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+    ops::RangeInclusive,
+    path::PathBuf,
+    sync::LazyLock,
+};
+
+use colored::Colorize;
+use log::info;
+use parking_lot::Mutex;
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{Color, Theme, ThemeSet},
+    html::{self, ClassStyle, IncludeBackground},
+    parsing::{ParseState, ScopeStack, SyntaxReference, SyntaxSet},
+    util::LinesWithEndings,
+};
+
+use crate::{
+    error::{Error, Result},
+    manifest::{HighlightConfig, HighlightStyle},
+    vfs::Vfs,
+};
+
+use super::{escape_xml, Markdown};
+
+/// Stylesheet generated for [`HighlightStyle::Class`], published at the root of site.
+pub(crate) const HIGHLIGHT_CSS: &str = "highlight.css";
+
+const CLASS_STYLE: ClassStyle = ClassStyle::SpacedPrefixed { prefix: "hl-" };
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEMES: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+/// Write [`HIGHLIGHT_CSS`] if `markdown` needs one, returning every file written.
+pub(crate) async fn render_stylesheet(markdown: &Markdown, fs: &dyn Vfs) -> Result<Vec<PathBuf>> {
+    let Some(css) = markdown.stylesheet() else {
+        return Ok(Vec::new());
+    };
+
+    let path = PathBuf::from(HIGHLIGHT_CSS);
+    info!(
+        "Rendering stylesheet `{}`",
+        path.to_str().unwrap().bold().underline()
+    );
+    fs.write(&path, css.into_bytes()).await?;
+
+    Ok(vec![path])
+}
+
+/// Highlighter of fenced code blocks, set up from manifest.
+pub(crate) struct Highlighter {
+    style: HighlightStyle,
+    theme: Theme,
+    line_numbers: bool,
+}
+
+impl Highlighter {
+    /// Highlighter of `config`, `None` if highlighting is turned off.
+    pub(crate) fn new(config: &HighlightConfig) -> Result<Option<Highlighter>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        let theme = THEMES
+            .themes
+            .get(&config.theme)
+            .ok_or_else(|| Error::HighlightThemeNotFound(config.theme.clone()))?;
+
+        Ok(Some(Highlighter {
+            style: config.style,
+            theme: theme.clone(),
+            line_numbers: config.line_numbers,
+        }))
+    }
+
+    /// Content of [`HIGHLIGHT_CSS`], only needed by [`HighlightStyle::Class`].
+    pub(crate) fn stylesheet(&self) -> Option<String> {
+        if self.style != HighlightStyle::Class {
+            return None;
+        }
+
+        let mut css = html::css_for_theme_with_class_style(&self.theme, CLASS_STYLE).ok()?;
+        let highlight = self.theme.settings.line_highlight.map(rgb);
+        css.push_str(&format!(
+            ".line.highlighted {{ display: inline-block; width: 100%; background-color: {}; }}\n",
+            highlight.as_deref().unwrap_or("rgba(255, 255, 0, 0.2)")
+        ));
+        css.push_str(".line-number { display: inline-block; min-width: 2em; margin-right: 1em; text-align: right; opacity: 0.5; user-select: none; }\n");
+        css.push_str(".code-title { font-family: monospace; font-weight: bold; }\n");
+
+        Some(css)
+    }
+
+    /// Adapter writing code blocks of a single Markdown document.
+    pub(super) fn adapter(&self) -> CodeAdapter<'_> {
+        CodeAdapter {
+            highlighter: self,
+            fence: Mutex::new(Fence::default()),
+        }
+    }
+
+    /// Highlighted HTML of every line of `code`, without line breaks.
+    fn lines(&self, syntax: &SyntaxReference, code: &str) -> Option<Vec<String>> {
+        let mut lines = Vec::new();
+
+        match self.style {
+            HighlightStyle::Inline => {
+                let mut highlight = HighlightLines::new(syntax, &self.theme);
+                for line in LinesWithEndings::from(code) {
+                    let ranges = highlight.highlight_line(line, &SYNTAXES).ok()?;
+                    lines.push(
+                        html::styled_line_to_highlighted_html(&ranges, IncludeBackground::No)
+                            .ok()?,
+                    );
+                }
+            }
+            HighlightStyle::Class => {
+                let mut state = ParseState::new(syntax);
+                let mut stack = ScopeStack::new();
+                for line in LinesWithEndings::from(code) {
+                    let ops = state.parse_line(line, &SYNTAXES).ok()?;
+                    let (html, _) =
+                        html::line_tokens_to_classed_spans(line, &ops, CLASS_STYLE, &mut stack)
+                            .ok()?;
+                    lines.push(html);
+                }
+            }
+        }
+
+        Some(balance(lines))
+    }
+}
+
+/// Writes code blocks for comrak, one at a time.
+///
+/// Comrak hands over info string of block to [`CodeAdapter::write_code_tag`] only, so it
+/// is kept until the code itself comes in [`CodeAdapter::write_highlighted`].
+pub(super) struct CodeAdapter<'a> {
+    highlighter: &'a Highlighter,
+    fence: Mutex<Fence>,
+}
+
+impl comrak::adapters::SyntaxHighlighterAdapter for CodeAdapter<'_> {
+    fn write_highlighted(
+        &self,
+        output: &mut dyn Write,
+        lang: Option<&str>,
+        code: &str,
+    ) -> io::Result<()> {
+        let fence = std::mem::take(&mut *self.fence.lock());
+        let syntax = lang
+            .filter(|it| !it.is_empty())
+            .and_then(|it| SYNTAXES.find_syntax_by_token(it))
+            .unwrap_or_else(|| SYNTAXES.find_syntax_plain_text());
+
+        let lines = self
+            .highlighter
+            .lines(syntax, code)
+            .unwrap_or_else(|| code.lines().map(escape_xml).collect());
+        let inline = self.highlighter.style == HighlightStyle::Inline;
+        let settings = &self.highlighter.theme.settings;
+
+        for (i, line) in lines.iter().enumerate() {
+            let number = i + 1;
+            let highlighted = fence.lines.iter().any(|it| it.contains(&number));
+
+            match (highlighted, inline) {
+                (false, _) => write!(output, r#"<span class="line">"#)?,
+                (true, false) => write!(output, r#"<span class="line highlighted">"#)?,
+                (true, true) => write!(
+                    output,
+                    r#"<span class="line highlighted" style="display:inline-block;width:100%;background-color:{};">"#,
+                    settings
+                        .line_highlight
+                        .map(rgb)
+                        .as_deref()
+                        .unwrap_or("rgba(255,255,0,0.2)")
+                )?,
+            }
+
+            if fence.line_numbers.unwrap_or(self.highlighter.line_numbers) {
+                if inline {
+                    write!(
+                        output,
+                        r#"<span class="line-number" style="display:inline-block;min-width:2em;margin-right:1em;text-align:right;opacity:0.5;user-select:none;">{number}</span>"#
+                    )?;
+                } else {
+                    write!(output, r#"<span class="line-number">{number}</span>"#)?;
+                }
+            }
+
+            writeln!(output, "{line}</span>")?;
+        }
+
+        Ok(())
+    }
+
+    fn write_pre_tag(
+        &self,
+        _output: &mut dyn Write,
+        _attributes: HashMap<String, String>,
+    ) -> io::Result<()> {
+        // Written along with code tag, once title of block is known.
+        Ok(())
+    }
+
+    fn write_code_tag(
+        &self,
+        output: &mut dyn Write,
+        attributes: HashMap<String, String>,
+    ) -> io::Result<()> {
+        let fence = Fence::parse(attributes.get("data-meta").map_or("", String::as_str));
+
+        if let Some(title) = &fence.title {
+            write!(
+                output,
+                r#"<div class="code-title">{}</div>"#,
+                escape_xml(title)
+            )?;
+        }
+
+        let settings = &self.highlighter.theme.settings;
+        match self.highlighter.style {
+            HighlightStyle::Class => write!(output, r#"<pre class="highlight hl-code">"#)?,
+            HighlightStyle::Inline => write!(
+                output,
+                r#"<pre class="highlight" style="background-color:{};color:{};">"#,
+                settings.background.map(rgb).unwrap_or_default(),
+                settings.foreground.map(rgb).unwrap_or_default(),
+            )?,
+        }
+
+        match attributes.get("class") {
+            Some(class) => write!(output, r#"<code class="{}">"#, escape_xml(class))?,
+            None => write!(output, "<code>")?,
+        }
+
+        *self.fence.lock() = fence;
+        Ok(())
+    }
+}
+
+/// Settings of a single block, written after language in its info string, such as
+/// `rust {3-5} title="main.rs" linenos`.
+#[derive(Debug, Default, PartialEq)]
+struct Fence {
+    title: Option<String>,
+
+    /// Highlighted lines, starting from 1.
+    lines: Vec<RangeInclusive<usize>>,
+
+    /// Number lines of block, overriding manifest.
+    line_numbers: Option<bool>,
+}
+
+impl Fence {
+    fn parse(meta: &str) -> Fence {
+        let mut fence = Fence::default();
+        let mut rest = meta.trim();
+
+        while !rest.is_empty() {
+            if let Some(ranges) = rest.strip_prefix('{') {
+                let (ranges, after) = ranges.split_once('}').unwrap_or((ranges, ""));
+                fence.lines.extend(ranges.split(',').flat_map(range));
+                rest = after;
+            } else if let Some((key, after)) = rest
+                .split_once('=')
+                .filter(|(key, _)| !key.contains(char::is_whitespace))
+            {
+                let (value, after) = match after.strip_prefix('"') {
+                    Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+                    None => after.split_once(char::is_whitespace).unwrap_or((after, "")),
+                };
+                match key {
+                    "title" | "filename" => fence.title = Some(value.to_owned()),
+                    "linenos" => fence.line_numbers = Some(value != "false"),
+                    _ => {}
+                }
+                rest = after;
+            } else {
+                let (word, after) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                if word == "linenos" {
+                    fence.line_numbers = Some(true);
+                }
+                rest = after;
+            }
+
+            rest = rest.trim_start();
+        }
+
+        fence
+    }
+}
+
+/// Lines of `3` or `3-5`.
+fn range(text: &str) -> Option<RangeInclusive<usize>> {
+    let text = text.trim();
+    let (start, end) = text.split_once('-').unwrap_or((text, text));
+
+    Some(start.trim().parse().ok()?..=end.trim().parse().ok()?)
+}
+
+/// Make every line stand alone, closing spans left open at its end and opening them
+/// again on the next line, so lines can be wrapped by their own elements.
+fn balance(lines: Vec<String>) -> Vec<String> {
+    let mut open = Vec::<String>::new();
+
+    lines
+        .into_iter()
+        .map(|line| {
+            let line = line.replace(['\r', '\n'], "");
+            let mut balanced = open.concat();
+            let mut rest = line.as_str();
+
+            while let Some(start) = rest.find('<') {
+                let end = rest[start..]
+                    .find('>')
+                    .map_or(rest.len(), |it| start + it + 1);
+                let tag = &rest[start..end];
+                if tag.starts_with("</") {
+                    open.pop();
+                } else {
+                    open.push(tag.to_owned());
+                }
+                balanced.push_str(&rest[..end]);
+                rest = &rest[end..];
+            }
+            balanced.push_str(rest);
+            balanced.push_str(&"</span>".repeat(open.len()));
+
+            balanced
+        })
+        .collect()
+}
+
+fn rgb(color: Color) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fence() {
+        assert_eq!(
+            Fence::parse(r#"{1,3-5} title="src/main.rs" linenos"#),
+            Fence {
+                title: Some("src/main.rs".to_owned()),
+                lines: vec![1..=1, 3..=5],
+                line_numbers: Some(true),
+            }
+        );
+        assert_eq!(Fence::parse("linenos=false").line_numbers, Some(false));
+    }
+
+    #[test]
+    fn test_balance() {
+        let lines = vec![r#"<span class="a">x"#.to_owned(), "y</span>\n".to_owned()];
+        assert_eq!(
+            balance(lines),
+            [r#"<span class="a">x</span>"#, r#"<span class="a">y</span>"#]
+        );
+    }
+}
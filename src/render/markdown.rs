@@ -0,0 +1,125 @@
+use comrak::{Options, Plugins};
+
+use crate::{
+    error::Result,
+    manifest::{Manifest, MarkdownOptions, TocConfig},
+    permalink::SlugStyle,
+};
+
+use super::{
+    highlight::Highlighter,
+    toc::{Headings, TocEntry},
+};
+
+/// Converter of Markdown into HTML fragments, set up from manifest once per build.
+#[derive(Default)]
+pub(crate) struct Markdown {
+    options: MarkdownOptions,
+    highlighter: Option<Highlighter>,
+
+    /// How ids of headings are made from their text.
+    slug: SlugStyle,
+    toc: TocConfig,
+}
+
+impl Markdown {
+    pub(crate) fn new(manifest: &Manifest) -> Result<Markdown> {
+        Ok(Markdown {
+            options: manifest.markdown.clone(),
+            highlighter: Highlighter::new(&manifest.highlight)?,
+            slug: manifest.slug,
+            toc: manifest.toc.clone(),
+        })
+    }
+
+    /// Convert Markdown into HTML fragment.
+    pub(crate) fn render(&self, content: &str) -> String {
+        self.render_with(content, &MarkdownOptions::default()).0
+    }
+
+    /// Convert Markdown of entry into HTML fragment with `overrides` of its meta, along
+    /// with its table of contents.
+    pub(crate) fn render_with(
+        &self,
+        content: &str,
+        overrides: &MarkdownOptions,
+    ) -> (String, Vec<TocEntry>) {
+        let mut options = comrak_options(&self.options.merge(overrides));
+        let mut plugins = Plugins::default();
+
+        let headings = Headings::new(self.slug, self.toc.anchors);
+        plugins.render.heading_adapter = Some(&headings);
+
+        let adapter = self.highlighter.as_ref().map(Highlighter::adapter);
+        if let Some(adapter) = &adapter {
+            // Settings of code blocks follow language in info string.
+            options.render.full_info_string = true;
+            plugins.render.codefence_syntax_highlighter = Some(adapter);
+        }
+
+        let html = comrak::markdown_to_html_with_plugins(content, &options, &plugins);
+        (html, headings.into_toc(self.toc.max_depth))
+    }
+
+    /// Stylesheet of highlighted code, published as [`super::HIGHLIGHT_CSS`] if any.
+    pub(crate) fn stylesheet(&self) -> Option<String> {
+        self.highlighter.as_ref()?.stylesheet()
+    }
+}
+
+fn comrak_options(config: &MarkdownOptions) -> Options<'static> {
+    let mut options = Options::default();
+
+    options.extension.table = config.table.unwrap_or_default();
+    options.extension.footnotes = config.footnotes.unwrap_or_default();
+    options.extension.strikethrough = config.strikethrough.unwrap_or_default();
+    options.extension.tasklist = config.tasklist.unwrap_or_default();
+    options.extension.autolink = config.autolink.unwrap_or_default();
+    options.extension.superscript = config.superscript.unwrap_or_default();
+    options.extension.description_lists = config.description_lists.unwrap_or_default();
+    options.extension.front_matter_delimiter = config.front_matter_delimiter.clone();
+    options.parse.smart = config.smart.unwrap_or_default();
+    options.render.unsafe_ = config.unsafe_html.unwrap_or_default();
+
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight() {
+        let manifest: Manifest = "site = \"Moxide\"".to_owned().try_into().unwrap();
+        let markdown = Markdown::new(&manifest).unwrap();
+
+        let html =
+            markdown.render("```rust {2} title=\"main.rs\"\nfn main() {\n    let a = 1;\n}\n```\n");
+        assert!(html.starts_with(r#"<div class="code-title">main.rs</div><pre class="highlight""#));
+        assert!(html.contains(r#"<code class="language-rust">"#));
+        assert_eq!(html.matches(r#"<span class="line highlighted""#).count(), 1);
+        assert_eq!(html.matches(r#"<span class="line">"#).count(), 2);
+    }
+
+    #[test]
+    fn test_options() {
+        let manifest: Manifest = "site = \"Moxide\"\n[markdown]\nstrikethrough = true"
+            .to_owned()
+            .try_into()
+            .unwrap();
+        let markdown = Markdown::new(&manifest).unwrap();
+        let content = "~~old~~ <b>raw</b>";
+
+        let html = markdown.render(content);
+        assert!(html.contains("<del>old</del>"));
+        assert!(html.contains("<!-- raw HTML omitted -->"));
+
+        let overrides = MarkdownOptions {
+            strikethrough: Some(false),
+            unsafe_html: Some(true),
+            ..MarkdownOptions::default()
+        };
+        let (html, _) = markdown.render_with(content, &overrides);
+        assert!(html.contains("~~old~~ <b>raw</b>"));
+    }
+}
@@ -1,6 +1,10 @@
 use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
     future::Future,
+    iter,
     ops::Deref,
+    path::{Path, PathBuf},
     pin::Pin,
     sync::{Arc, OnceLock},
 };
@@ -9,16 +13,34 @@ use async_trait::async_trait;
 use bundle::BundleRender;
 use colored::Colorize;
 use log::{info, trace};
+use minijinja::context;
 use page::PageRender;
 use parking_lot::RwLock;
 
 use crate::{
-    error::{Error, Result},
-    mkentry::MarkdownEntryContext,
+    error::{BuildFailure, Error, Phase, Result},
+    mkentry::{MarkdownEntryContext, SECTION_INDEX},
+    permalink::{self, Permalink},
+    site::{paginate, Home, Section, Site, Taxonomy, Term},
+    theme::{PageContext, ResourceContext, Theme},
+    vfs::{Recording, Vfs},
 };
 
+pub(crate) use feed::{feed_links, feeds_of, render_feed, Feed};
+pub(crate) use highlight::{render_stylesheet, HIGHLIGHT_CSS};
+pub(crate) use markdown::Markdown;
+pub(crate) use search::render_search;
+pub(crate) use sitemap::{render_robots, render_sitemap};
+pub(crate) use toc::TocEntry;
+
 mod bundle;
+mod feed;
+mod highlight;
+mod markdown;
 mod page;
+mod search;
+mod sitemap;
+mod toc;
 
 #[async_trait]
 pub(crate) trait Render: Send + Sync {
@@ -83,9 +105,10 @@ impl RenderRegistry {
         let rendering = async move {
             let renderer = &ctx.entry.meta.renderer;
             trace!(
-                "Detected render `{}` for entry `{}`.",
+                "Detected render `{}` for entry `{}` at {}.",
                 ctx.entry.meta.renderer.italic().underline(),
-                ctx.entry.meta.title.bold()
+                ctx.entry.meta.title.bold(),
+                ctx.index.to_str().unwrap().underline()
             );
 
             let render = Self::new().fetch(renderer);
@@ -99,3 +122,309 @@ impl RenderRegistry {
         Ok(Box::pin(rendering))
     }
 }
+
+/// Output of a rendered entry.
+pub(crate) struct Rendered {
+    pub(crate) output: PathBuf,
+
+    /// Every file written by render, so entries nested under output are left alone
+    /// once it is dropped.
+    pub(crate) files: HashSet<PathBuf>,
+
+    /// Content rendered as HTML, reused by feeds while entry stays the same.
+    pub(crate) content: Option<String>,
+}
+
+/// Render entries of `contexts` all at once, returning output of each keyed by its index
+/// markdown. Failures are recorded into `failed`.
+pub(crate) async fn render_entries(
+    contexts: Vec<MarkdownEntryContext>,
+    failed: &mut BTreeMap<PathBuf, BuildFailure>,
+) -> HashMap<PathBuf, Rendered> {
+    let mut renders = Vec::new();
+    let mut output = Vec::new();
+
+    for mut ctx in contexts {
+        let recording = Arc::new(Recording::new(ctx.fs.clone()));
+        ctx.fs = recording.clone();
+        let render = (
+            ctx.index.clone(),
+            ctx.output.clone(),
+            recording,
+            ctx.content.clone(),
+        );
+
+        match RenderRegistry::to_prepared_render(ctx) {
+            Ok(prepared) => {
+                renders.push(render);
+                output.push(prepared);
+            }
+            Err(cause) => render_failed(failed, render.0, cause),
+        }
+    }
+
+    let results = futures::future::join_all(output).await;
+    let mut rendered = HashMap::new();
+    for ((index, output, recording, content), result) in renders.into_iter().zip(results) {
+        let files = recording.written();
+        let content = content.get().cloned();
+        rendered.insert(
+            index.clone(),
+            Rendered {
+                output,
+                files,
+                content,
+            },
+        );
+
+        if let Err(cause) = result {
+            render_failed(failed, index, cause);
+        }
+    }
+
+    rendered
+}
+
+/// Record failure of rendering `index`.
+pub(crate) fn render_failed(
+    failed: &mut BTreeMap<PathBuf, BuildFailure>,
+    index: PathBuf,
+    cause: Error,
+) {
+    trace!(
+        "Failed rendering {}: {cause}",
+        index.to_str().unwrap().bold().underline()
+    );
+    let phase = Phase::Render;
+    failed.insert(index, BuildFailure { phase, cause });
+}
+
+/// Render entry with theme template `page.html` into `index.html` under its output.
+async fn render_page(ctx: &MarkdownEntryContext, resources: Vec<ResourceContext>) -> Result<()> {
+    let overrides = &ctx.entry.meta.markdown;
+    let (content, toc) = ctx.markdown.render_with(&ctx.entry.body(), overrides);
+    let _ = ctx.content.set(content.clone());
+    let page = PageContext {
+        resources,
+        taxonomies: ctx
+            .site
+            .page(&ctx.index)
+            .map(|it| it.taxonomies.clone())
+            .unwrap_or_default(),
+        toc: if ctx.entry.meta.toc == Some(false) {
+            Vec::new()
+        } else {
+            toc
+        },
+        ..PageContext::new(
+            &ctx.entry.meta,
+            permalink::url(&ctx.output),
+            ctx.markdown
+                .render_with(&ctx.entry.description, overrides)
+                .0,
+            content,
+        )
+    };
+    let html = ctx
+        .theme
+        .render("page.html", context! { page, site => &*ctx.site })?;
+
+    ctx.fs
+        .write(&ctx.output.join("index.html"), html.into_bytes())
+        .await
+}
+
+/// Page generated from site rather than from an entry.
+pub(crate) enum Listing<'a> {
+    /// Recent pages, rendered by `index.html`.
+    Home(&'a Home),
+
+    /// Pages of section, rendered by `list.html`.
+    Section(&'a Section),
+
+    /// Terms of taxonomy, rendered by template of taxonomy.
+    Taxonomy(&'a Taxonomy),
+
+    /// Pages of a term, rendered by term template of taxonomy.
+    Term(&'a Taxonomy, &'a Term),
+}
+
+impl fmt::Display for Listing<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Listing::Home(_) => write!(f, "home page"),
+            Listing::Section(section) => write!(f, "list of section `{}`", section.title),
+            Listing::Taxonomy(taxonomy) => write!(f, "list of taxonomy `{}`", taxonomy.name),
+            Listing::Term(taxonomy, term) => {
+                write!(f, "list of term `{}` in `{}`", term.name, taxonomy.name)
+            }
+        }
+    }
+}
+
+impl Listing<'_> {
+    fn title(&self) -> &str {
+        match self {
+            Listing::Home(_) => "home",
+            Listing::Section(section) => &section.title,
+            Listing::Taxonomy(taxonomy) => &taxonomy.name,
+            Listing::Term(_, term) => &term.name,
+        }
+    }
+}
+
+/// Every list of `site` as `(source, output, listing)`, source being blamed once list
+/// fails.
+///
+/// Root section is listed by home page, others by their own list page. Lists without
+/// a source of their own are blamed on `manifest`, and terms on their newest page.
+pub(crate) fn lists_of<'a>(
+    site: &'a Site,
+    permalink: &Permalink,
+    src: &Path,
+    manifest: &Path,
+) -> Vec<(PathBuf, PathBuf, Listing<'a>)> {
+    let home_index = Some(src.join(SECTION_INDEX))
+        .filter(|it| it.is_file())
+        .unwrap_or_else(|| manifest.to_path_buf());
+    let home = iter::once((home_index, PathBuf::new(), Listing::Home(&site.home)));
+    let sections = site
+        .sections
+        .values()
+        .filter(|it| !it.path.is_empty())
+        .map(|it| {
+            let index = src.join(&it.path).join(SECTION_INDEX);
+            let output = PathBuf::from(permalink.section(&it.path));
+            (index, output, Listing::Section(it))
+        });
+    let taxonomies = site.taxonomies.values().flat_map(|taxonomy| {
+        let output = PathBuf::from(permalink.taxonomy(&taxonomy.name, None));
+        let terms = taxonomy.terms.iter().map(|term| {
+            let index = term.pages[0].index.clone();
+            let output = PathBuf::from(permalink.taxonomy(&taxonomy.name, Some(&term.name)));
+            (index, output, Listing::Term(taxonomy, term))
+        });
+
+        iter::once((manifest.to_path_buf(), output, Listing::Taxonomy(taxonomy))).chain(terms)
+    });
+
+    home.chain(sections).chain(taxonomies).collect()
+}
+
+/// Render every list of [`lists_of`] unless its output is taken by an entry of `rendered`
+/// or an earlier list, returning every directory written or failed writing. Failures are
+/// recorded into `failed` against source of list.
+pub(crate) async fn render_lists(
+    theme: &Theme,
+    site: &Site,
+    lists: Vec<(PathBuf, PathBuf, Listing<'_>)>,
+    rendered: &HashMap<PathBuf, Rendered>,
+    fs: &dyn Vfs,
+    failed: &mut BTreeMap<PathBuf, BuildFailure>,
+) -> HashSet<PathBuf> {
+    let mut claimed = rendered
+        .iter()
+        .map(|(index, it)| (it.output.clone(), index.clone()))
+        .collect::<HashMap<_, _>>();
+    let mut generated = HashSet::new();
+
+    for (index, output, listing) in lists {
+        if let Some(by) = claimed.get(&output) {
+            let cause = Error::ListTaken {
+                list: listing.to_string(),
+                url: permalink::url(&output),
+                by: by.clone(),
+            };
+            let phase = Phase::Permalink;
+            failed.insert(index, BuildFailure { phase, cause });
+            continue;
+        }
+
+        match render_listing(theme, site, &listing, fs, &output).await {
+            Ok(written) => generated.extend(written),
+            Err(cause) => render_failed(failed, index.clone(), cause),
+        }
+
+        claimed.insert(output.clone(), index);
+        generated.insert(output);
+    }
+
+    generated
+}
+
+/// Render `listing` with its theme template into `index.html` under `output`, returning
+/// every directory written.
+///
+/// Paginated lists put their first page under `output` as well, and others under
+/// `output/page/<n>/`, while `output/page/1/` redirects to the first one.
+async fn render_listing(
+    theme: &Theme,
+    site: &Site,
+    listing: &Listing<'_>,
+    fs: &dyn Vfs,
+    output: &Path,
+) -> Result<Vec<PathBuf>> {
+    info!(
+        "Rendering list `{}` to `{}`",
+        listing.title().bold(),
+        output.to_str().unwrap().bold().underline()
+    );
+
+    let (pages, size, url) = match listing {
+        Listing::Home(home) => (&home.pages[..], home.paginate_by, &home.url),
+        Listing::Section(section) => (&section.pages[..], section.paginate_by, &section.url),
+        Listing::Taxonomy(taxonomy) => (&[][..], None, &taxonomy.url),
+        Listing::Term(taxonomy, term) => (&term.pages[..], taxonomy.paginate_by, &term.url),
+    };
+    let paginators = paginate(pages, size, url);
+    let mut written = Vec::new();
+
+    if paginators.len() > 1 {
+        let alias = output.join("page").join("1");
+        fs.write(&alias.join("index.html"), redirect(url).into_bytes())
+            .await?;
+        written.push(alias);
+    }
+
+    for paginator in paginators {
+        let html = match listing {
+            Listing::Home(home) => {
+                theme.render("index.html", context! { home, paginator, site })?
+            }
+            Listing::Section(section) => {
+                theme.render("list.html", context! { section, paginator, site })?
+            }
+            Listing::Taxonomy(taxonomy) => {
+                theme.render(&taxonomy.template, context! { taxonomy, site })?
+            }
+            Listing::Term(taxonomy, term) => theme.render(
+                &taxonomy.term_template,
+                context! { taxonomy, term, paginator, site },
+            )?,
+        };
+
+        let dir = match paginator.current {
+            1 => output.to_path_buf(),
+            current => output.join("page").join(current.to_string()),
+        };
+        fs.write(&dir.join("index.html"), html.into_bytes()).await?;
+        written.push(dir);
+    }
+
+    Ok(written)
+}
+
+/// Escape `text` for XML text and attribute values.
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Page sending browsers to `url` right away.
+fn redirect(url: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html><html><head><meta charset="utf-8"><link rel="canonical" href="{url}"><meta http-equiv="refresh" content="0; url={url}"></head></html>"#
+    )
+}
@@ -18,6 +18,6 @@ impl Render for PageRender {
             ctx.output.to_str().unwrap().bold().underline()
         );
 
-        Ok(())
+        super::render_page(&ctx, Vec::new()).await
     }
 }
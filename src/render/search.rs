@@ -0,0 +1,40 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use colored::Colorize;
+use log::info;
+
+use crate::{
+    error::Result,
+    manifest::SearchConfig,
+    search::{SearchIndex, SearchText, CLIENT},
+    site::Site,
+    vfs::Vfs,
+};
+
+/// Write `search_index.json` of pages picked by `config`, along with `search.js` querying
+/// it, returning every file written.
+///
+/// `texts` holds plain text of pages, keyed by their index markdown.
+pub(crate) async fn render_search(
+    site: &Site,
+    config: &SearchConfig,
+    texts: &HashMap<PathBuf, SearchText>,
+    fs: &dyn Vfs,
+) -> Result<Vec<PathBuf>> {
+    let index = SearchIndex::new(config, &site.pages, texts);
+    let path = Path::new("search_index.json");
+    info!(
+        "Rendering search index of {} pages to `{}`",
+        index.pages.len(),
+        path.to_str().unwrap().bold().underline()
+    );
+    fs.write(path, serde_json::to_vec(&index)?).await?;
+
+    let client = Path::new("search.js");
+    fs.write(client, CLIENT.as_bytes().to_vec()).await?;
+
+    Ok(vec![path.to_path_buf(), client.to_path_buf()])
+}
@@ -0,0 +1,205 @@
+use std::{fmt::Write, iter, path::PathBuf};
+
+use chrono::{DateTime, FixedOffset, SecondsFormat};
+use colored::Colorize;
+use log::{info, warn};
+
+use crate::{
+    error::Result,
+    manifest::RobotsConfig,
+    site::{Section, Site, SitePage},
+    vfs::Vfs,
+};
+
+use super::escape_xml;
+
+/// Most URLs a single sitemap may hold, larger sites get a sitemap index instead.
+const MAX_URLS: usize = 50_000;
+
+/// Write `sitemap.xml` listing every page and list of `site`, returning every file written.
+///
+/// Past [`MAX_URLS`] URLs, `sitemap.xml` indexes `sitemap-<n>.xml` holding them. Nothing
+/// is written without `base_url` of site, since sitemaps only take absolute URLs.
+pub(crate) async fn render_sitemap(site: &Site, fs: &dyn Vfs) -> Result<Vec<PathBuf>> {
+    let mut written = Vec::new();
+
+    if site.base_url.is_empty() {
+        warn!("Skipping sitemap, `base_url` of manifest is not set.");
+        return Ok(written);
+    }
+
+    for (name, xml) in sitemaps(&site.base_url, &urls_of(site), MAX_URLS) {
+        let path = PathBuf::from(name);
+        info!(
+            "Rendering sitemap `{}`",
+            path.to_str().unwrap().bold().underline()
+        );
+        fs.write(&path, xml.into_bytes()).await?;
+        written.push(path);
+    }
+
+    Ok(written)
+}
+
+/// Write `robots.txt` with rules of `config`, referring to sitemap if published.
+pub(crate) async fn render_robots(
+    site: &Site,
+    config: &RobotsConfig,
+    sitemap: bool,
+    fs: &dyn Vfs,
+) -> Result<Vec<PathBuf>> {
+    let mut robots = format!("User-agent: {}\n", config.user_agent);
+    for path in &config.allow {
+        let _ = writeln!(robots, "Allow: {path}");
+    }
+    for path in &config.disallow {
+        let _ = writeln!(robots, "Disallow: {path}");
+    }
+    if config.allow.is_empty() && config.disallow.is_empty() {
+        robots.push_str("Disallow:\n");
+    }
+    if sitemap && !site.base_url.is_empty() {
+        let _ = writeln!(robots, "\nSitemap: {}/sitemap.xml", site.base_url);
+    }
+
+    let path = PathBuf::from("robots.txt");
+    info!(
+        "Rendering robots `{}`",
+        path.to_str().unwrap().bold().underline()
+    );
+    fs.write(&path, robots.into_bytes()).await?;
+
+    Ok(vec![path])
+}
+
+/// URL of every page and first page of every list, along with when it last changed.
+fn urls_of(site: &Site) -> Vec<(String, Option<DateTime<FixedOffset>>)> {
+    let lastmod = |pages: &mut dyn Iterator<Item = &SitePage>| pages.map(SitePage::modified).max();
+
+    let home = (site.home.url.clone(), lastmod(&mut site.pages.iter()));
+    let sections = site
+        .sections
+        .values()
+        .filter(|it| !it.path.is_empty())
+        .map(|section| {
+            let mut pages = site
+                .pages
+                .iter()
+                .filter(|it| Section::is_within(&it.section, &section.path));
+            (section.url.clone(), lastmod(&mut pages))
+        });
+    let pages = site
+        .pages
+        .iter()
+        .map(|it| (it.url.clone(), Some(it.modified())));
+    let taxonomies = site.taxonomies.values().flat_map(|taxonomy| {
+        let mut pages = taxonomy.terms.iter().flat_map(|it| &it.pages);
+        let terms = taxonomy
+            .terms
+            .iter()
+            .map(|it| (it.url.clone(), lastmod(&mut it.pages.iter())));
+
+        iter::once((taxonomy.url.clone(), lastmod(&mut pages))).chain(terms)
+    });
+
+    iter::once(home)
+        .chain(sections)
+        .chain(pages)
+        .chain(taxonomies)
+        .collect()
+}
+
+/// Sitemaps of `urls` as `(file name, XML)`, split into ones of at most `max` URLs and
+/// indexed by `sitemap.xml` if there are too many.
+fn sitemaps(
+    base_url: &str,
+    urls: &[(String, Option<DateTime<FixedOffset>>)],
+    max: usize,
+) -> Vec<(String, String)> {
+    if urls.len() <= max {
+        return vec![("sitemap.xml".to_owned(), urlset(base_url, urls))];
+    }
+
+    let chunks = urls
+        .chunks(max)
+        .enumerate()
+        .map(|(i, chunk)| (format!("sitemap-{}.xml", i + 1), chunk))
+        .collect::<Vec<_>>();
+
+    let mut index = String::new();
+    index.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    index.push_str(r#"<sitemapindex xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
+    for (name, chunk) in &chunks {
+        index.push_str("<sitemap>");
+        let _ = write!(
+            index,
+            "<loc>{}</loc>",
+            escape_xml(&format!("{base_url}/{name}"))
+        );
+        if let Some(lastmod) = chunk.iter().flat_map(|(_, it)| *it).max() {
+            let _ = write!(index, "<lastmod>{}</lastmod>", w3c(&lastmod));
+        }
+        index.push_str("</sitemap>");
+    }
+    index.push_str("</sitemapindex>");
+
+    iter::once(("sitemap.xml".to_owned(), index))
+        .chain(
+            chunks
+                .into_iter()
+                .map(|(name, chunk)| (name, urlset(base_url, chunk))),
+        )
+        .collect()
+}
+
+fn urlset(base_url: &str, urls: &[(String, Option<DateTime<FixedOffset>>)]) -> String {
+    let mut xml = String::new();
+    xml.push_str(r#"<?xml version="1.0" encoding="utf-8"?>"#);
+    xml.push_str(r#"<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">"#);
+    for (url, lastmod) in urls {
+        xml.push_str("<url>");
+        let _ = write!(
+            xml,
+            "<loc>{}</loc>",
+            escape_xml(&format!("{base_url}{url}"))
+        );
+        if let Some(lastmod) = lastmod {
+            let _ = write!(xml, "<lastmod>{}</lastmod>", w3c(lastmod));
+        }
+        xml.push_str("</url>");
+    }
+    xml.push_str("</urlset>");
+    xml
+}
+
+fn w3c(date: &DateTime<FixedOffset>) -> String {
+    date.to_rfc3339_opts(SecondsFormat::Secs, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+
+    use super::*;
+
+    #[test]
+    fn test_sitemaps() {
+        let urls = ["/", "/a/", "/b/"]
+            .map(|it| (it.to_owned(), Some(Local::now().fixed_offset())))
+            .to_vec();
+
+        let single = sitemaps("https://example.com", &urls, 3);
+        assert_eq!(single.len(), 1);
+        assert!(single[0].1.contains("<loc>https://example.com/a/</loc>"));
+
+        let split = sitemaps("https://example.com", &urls, 2);
+        assert_eq!(
+            split.iter().map(|(it, _)| it.as_str()).collect::<Vec<_>>(),
+            ["sitemap.xml", "sitemap-1.xml", "sitemap-2.xml"]
+        );
+        assert!(split[0]
+            .1
+            .contains("<loc>https://example.com/sitemap-2.xml</loc>"));
+        assert!(!split[2].1.contains("/a/"));
+    }
+}
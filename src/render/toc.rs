@@ -0,0 +1,145 @@
+use std::{
+    collections::HashSet,
+    io::{self, Write},
+};
+
+use comrak::{
+    adapters::{HeadingAdapter, HeadingMeta},
+    nodes::Sourcepos,
+};
+use parking_lot::Mutex;
+use serde::Serialize;
+
+use crate::permalink::{slugify, SlugStyle};
+
+use super::escape_xml;
+
+/// Heading in table of contents, exposed to templates as `page.toc`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct TocEntry {
+    pub(crate) level: u8,
+    pub(crate) id: String,
+    pub(crate) title: String,
+
+    /// Headings nested under this one.
+    pub(crate) children: Vec<TocEntry>,
+}
+
+/// Writes headings of a single Markdown document with unique ids, collecting them along
+/// the way.
+pub(super) struct Headings {
+    style: SlugStyle,
+    anchors: bool,
+    ids: Mutex<HashSet<String>>,
+    headings: Mutex<Vec<TocEntry>>,
+}
+
+impl Headings {
+    pub(super) fn new(style: SlugStyle, anchors: bool) -> Headings {
+        Headings {
+            style,
+            anchors,
+            ids: Mutex::new(HashSet::new()),
+            headings: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Id of heading titled `title`, numbered after the same ones used before.
+    fn id_of(&self, title: &str) -> String {
+        let slug = slugify(title, self.style);
+        let mut ids = self.ids.lock();
+        let mut id = slug.clone();
+
+        for n in 1.. {
+            if !ids.contains(&id) {
+                break;
+            }
+            id = format!("{slug}-{n}");
+        }
+
+        ids.insert(id.clone());
+        id
+    }
+
+    /// Nested table of contents of headings up to `max_depth`.
+    pub(super) fn into_toc(self, max_depth: u8) -> Vec<TocEntry> {
+        let mut toc = Vec::new();
+
+        for heading in self.headings.into_inner() {
+            if heading.level <= max_depth {
+                nest(&mut toc, heading);
+            }
+        }
+
+        toc
+    }
+}
+
+impl HeadingAdapter for Headings {
+    fn enter(
+        &self,
+        output: &mut dyn Write,
+        heading: &HeadingMeta,
+        _sourcepos: Option<Sourcepos>,
+    ) -> io::Result<()> {
+        let id = self.id_of(&heading.content);
+        write!(output, r#"<h{} id="{}">"#, heading.level, escape_xml(&id))?;
+
+        self.headings.lock().push(TocEntry {
+            level: heading.level,
+            id,
+            title: heading.content.clone(),
+            children: Vec::new(),
+        });
+        Ok(())
+    }
+
+    fn exit(&self, output: &mut dyn Write, heading: &HeadingMeta) -> io::Result<()> {
+        if self.anchors {
+            if let Some(last) = self.headings.lock().last() {
+                write!(
+                    output,
+                    r##"<a class="anchor" href="#{}" aria-hidden="true">#</a>"##,
+                    escape_xml(&last.id)
+                )?;
+            }
+        }
+
+        writeln!(output, "</h{}>", heading.level)
+    }
+}
+
+/// Put `heading` under the last one of `toc` if that is of a higher level.
+fn nest(toc: &mut Vec<TocEntry>, heading: TocEntry) {
+    match toc.last_mut() {
+        Some(last) if last.level < heading.level => nest(&mut last.children, heading),
+        _ => toc.push(heading),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toc() {
+        let headings = Headings::new(SlugStyle::Unicode, false);
+        let mut output = Vec::new();
+        for (level, content) in [(2, "简介"), (3, "Setup"), (4, "Deep"), (2, "简介")] {
+            let heading = HeadingMeta {
+                level,
+                content: content.to_owned(),
+            };
+            headings.enter(&mut output, &heading, None).unwrap();
+            headings.exit(&mut output, &heading).unwrap();
+        }
+
+        let toc = headings.into_toc(3);
+        assert_eq!(
+            toc.iter().map(|it| it.id.as_str()).collect::<Vec<_>>(),
+            ["简介", "简介-1"]
+        );
+        assert_eq!(toc[0].children[0].id, "setup");
+        assert!(toc[0].children[0].children.is_empty());
+    }
+}
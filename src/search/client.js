@@ -0,0 +1,118 @@
+// Search client of moxide, querying `search_index.json` published along with site.
+//
+// Pages holding `<input id="search">` and `<ul id="search-results">` get results listed
+// while typing, others may call `moxideSearch(query)` resolving to matching pages.
+(function () {
+  // Keep in line with `is_cjk` of `src/search/tokenize.rs`.
+  const CJK = /[\u3040-\u30ff\u3400-\u4dbf\u4e00-\u9fff\uf900-\ufaff\uac00-\ud7af]/;
+  const WORD = /[\p{L}\p{N}]/u;
+  const loading = {};
+
+  function tokenize(text) {
+    const tokens = [];
+    let word = "";
+    let prev = null;
+
+    for (const c of text.toLowerCase()) {
+      if (CJK.test(c)) {
+        if (word) {
+          tokens.push(word);
+          word = "";
+        }
+        tokens.push(c);
+        if (prev) {
+          tokens.push(prev + c);
+        }
+        prev = c;
+      } else {
+        prev = null;
+        if (WORD.test(c)) {
+          word += c;
+        } else if (word) {
+          tokens.push(word);
+          word = "";
+        }
+      }
+    }
+
+    if (word) {
+      tokens.push(word);
+    }
+    return tokens;
+  }
+
+  function load(url) {
+    loading[url] = loading[url] || fetch(url).then((it) => it.json());
+    return loading[url];
+  }
+
+  // Pages holding every term of `query`, best first. The last word matches as prefix,
+  // so results show up while still typing.
+  async function search(query, url = "/search_index.json") {
+    const index = await load(url);
+    const tokens = [...new Set(tokenize(query))];
+    let scores = null;
+
+    tokens.forEach((token, i) => {
+      const prefix = i === tokens.length - 1 && !CJK.test(token);
+      const terms = prefix
+        ? Object.keys(index.terms).filter((it) => it.startsWith(token))
+        : [token];
+      const found = new Map();
+
+      for (const term of terms) {
+        for (const [page, score] of index.terms[term] || []) {
+          found.set(page, (found.get(page) || 0) + score);
+        }
+      }
+
+      scores = new Map(
+        [...(scores || found)]
+          .filter(([page]) => found.has(page))
+          .map(([page, score]) => [page, scores ? score + found.get(page) : score]),
+      );
+    });
+
+    return [...(scores || [])]
+      .sort((a, b) => b[1] - a[1])
+      .map(([page]) => index.pages[page]);
+  }
+
+  function show(list, pages) {
+    list.replaceChildren(
+      ...pages.slice(0, 20).map((page) => {
+        const item = document.createElement("li");
+        const link = document.createElement("a");
+        link.href = page.url;
+        link.textContent = page.title;
+        item.append(link);
+
+        if (page.summary) {
+          const summary = document.createElement("p");
+          summary.textContent = page.summary;
+          item.append(summary);
+        }
+        return item;
+      }),
+    );
+  }
+
+  window.moxideSearch = search;
+
+  document.addEventListener("DOMContentLoaded", () => {
+    const input = document.getElementById("search");
+    const list = document.getElementById("search-results");
+    if (!input || !list) {
+      return;
+    }
+
+    const url = input.dataset.index || "/search_index.json";
+    input.addEventListener("input", async () => {
+      const query = input.value;
+      const pages = await search(query, url);
+      if (query === input.value) {
+        show(list, pages);
+      }
+    });
+  });
+})();
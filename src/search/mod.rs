@@ -0,0 +1,169 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+};
+
+use comrak::{nodes::NodeValue, Arena, Options};
+use serde::Serialize;
+
+use crate::{manifest::SearchConfig, site::SitePage};
+
+pub(crate) use tokenize::tokenize;
+
+mod tokenize;
+
+/// Client querying the index in browsers, published as `search.js`.
+pub(crate) const CLIENT: &str = include_str!("client.js");
+
+/// Score a term earns from every occurrence in title, tags, description and body.
+const TITLE_WEIGHT: u32 = 8;
+const TAG_WEIGHT: u32 = 4;
+const DESCRIPTION_WEIGHT: u32 = 2;
+const BODY_WEIGHT: u32 = 1;
+
+/// Characters of description kept as summary of result.
+const SUMMARY_LENGTH: usize = 160;
+
+/// Inverted index of pages, published as `search_index.json`.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct SearchIndex {
+    pub(crate) pages: Vec<SearchPage>,
+
+    /// Every term, along with pages holding it as `[page, score]`.
+    pub(crate) terms: BTreeMap<String, Vec<(usize, u32)>>,
+}
+
+/// Page shown as a search result.
+#[derive(Debug, Serialize)]
+pub(crate) struct SearchPage {
+    pub(crate) title: String,
+    pub(crate) url: String,
+    pub(crate) date: String,
+    pub(crate) tags: Vec<String>,
+    pub(crate) summary: String,
+}
+
+/// Plain text of description and body of an entry.
+#[derive(Debug, Default)]
+pub(crate) struct SearchText {
+    pub(crate) description: String,
+    pub(crate) body: String,
+}
+
+impl SearchText {
+    pub(crate) fn new(description: &str, body: &str) -> SearchText {
+        SearchText {
+            description: plain_text(description),
+            body: plain_text(body),
+        }
+    }
+}
+
+impl SearchIndex {
+    /// Index `pages` in sections picked by `config`, with text of entries keyed by their
+    /// index markdown.
+    pub(crate) fn new(
+        config: &SearchConfig,
+        pages: &[SitePage],
+        texts: &HashMap<PathBuf, SearchText>,
+    ) -> SearchIndex {
+        let mut index = SearchIndex::default();
+        let empty = SearchText::default();
+
+        for page in pages.iter().filter(|it| config.includes(&it.section)) {
+            let text = texts.get(&page.index).unwrap_or(&empty);
+            let id = index.pages.len();
+            let mut scores = HashMap::<String, u32>::new();
+
+            let fields = [
+                (page.title.as_str(), TITLE_WEIGHT),
+                (&text.description, DESCRIPTION_WEIGHT),
+                (&text.body, BODY_WEIGHT),
+            ]
+            .into_iter()
+            .chain(page.tags.iter().map(|it| (it.as_str(), TAG_WEIGHT)));
+
+            for (field, weight) in fields {
+                for token in tokenize(field) {
+                    *scores.entry(token).or_default() += weight;
+                }
+            }
+
+            for (token, score) in scores {
+                index.terms.entry(token).or_default().push((id, score));
+            }
+
+            index.pages.push(SearchPage {
+                title: page.title.clone(),
+                url: page.url.clone(),
+                date: page.date.format("%Y-%m-%d").to_string(),
+                tags: page.tags.clone(),
+                summary: summary_of(&text.description),
+            });
+        }
+
+        index
+    }
+}
+
+/// Text of Markdown without any markup.
+pub(crate) fn plain_text(markdown: &str) -> String {
+    let arena = Arena::new();
+    let root = comrak::parse_document(&arena, markdown, &Options::default());
+    let mut text = String::new();
+
+    for node in root.descendants() {
+        match &node.data.borrow().value {
+            NodeValue::Text(it) => text.push_str(it),
+            NodeValue::Code(it) => text.push_str(&it.literal),
+            NodeValue::CodeBlock(it) => text.push_str(&it.literal),
+            NodeValue::SoftBreak | NodeValue::LineBreak => text.push(' '),
+            NodeValue::Paragraph | NodeValue::Heading(_) | NodeValue::Item(_) => text.push('\n'),
+            _ => {}
+        }
+    }
+
+    text.trim().to_owned()
+}
+
+fn summary_of(description: &str) -> String {
+    let description = description.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    match description.char_indices().nth(SUMMARY_LENGTH) {
+        Some((end, _)) => format!("{}…", &description[..end]),
+        None => description,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_index() {
+        let page = |title: &str, section: &str| SitePage {
+            index: PathBuf::from(title),
+            title: title.to_owned(),
+            section: section.to_owned(),
+            ..SitePage::default()
+        };
+        let pages = [page("Rust 入门", "blog"), page("About", "private")];
+        let texts = [(
+            PathBuf::from("Rust 入门"),
+            SearchText::new("**Hello**, `world`", "正文"),
+        )]
+        .into();
+        let config = SearchConfig {
+            exclude: vec!["private".to_owned()],
+            ..SearchConfig::default()
+        };
+
+        let index = SearchIndex::new(&config, &pages, &texts);
+        assert_eq!(index.pages.len(), 1);
+        assert_eq!(index.pages[0].summary, "Hello, world");
+        assert_eq!(index.terms["rust"], [(0, TITLE_WEIGHT)]);
+        assert_eq!(index.terms["入门"], [(0, TITLE_WEIGHT)]);
+        assert_eq!(index.terms["正文"], [(0, BODY_WEIGHT)]);
+        assert!(!index.terms.contains_key("about"));
+    }
+}
@@ -0,0 +1,61 @@
+/// Split `text` into lowercase terms for search.
+///
+/// Words are runs of letters and digits, except for Chinese, Japanese and Korean text
+/// not separated by spaces, which gives every single character and every pair of
+/// adjacent ones instead. Keep it in line with `tokenize` of `client.js`.
+pub(crate) fn tokenize(text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut prev = None;
+
+    for c in text.chars().flat_map(char::to_lowercase) {
+        if is_cjk(c) {
+            if !word.is_empty() {
+                tokens.push(std::mem::take(&mut word));
+            }
+            tokens.push(c.to_string());
+            if let Some(prev) = prev {
+                tokens.push(format!("{prev}{c}"));
+            }
+            prev = Some(c);
+        } else {
+            prev = None;
+            if c.is_alphanumeric() {
+                word.push(c);
+            } else if !word.is_empty() {
+                tokens.push(std::mem::take(&mut word));
+            }
+        }
+    }
+
+    if !word.is_empty() {
+        tokens.push(word);
+    }
+
+    tokens
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}'     // Hiragana and Katakana
+        | '\u{3400}'..='\u{4dbf}'   // CJK Extension A
+        | '\u{4e00}'..='\u{9fff}'   // CJK Unified Ideographs
+        | '\u{f900}'..='\u{faff}'   // CJK Compatibility Ideographs
+        | '\u{ac00}'..='\u{d7af}'   // Hangul Syllables
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(tokenize("Hello, Wörld 42"), ["hello", "wörld", "42"]);
+        assert_eq!(tokenize("Rust搜索"), ["rust", "搜", "索", "搜索"]);
+        assert_eq!(
+            tokenize("すし、寿司"),
+            ["す", "し", "すし", "寿", "司", "寿司"]
+        );
+    }
+}
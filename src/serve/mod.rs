@@ -0,0 +1,425 @@
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, StatusCode, Uri},
+    response::{
+        sse::{Event, KeepAlive},
+        Html, IntoResponse, Response, Sse,
+    },
+    routing::get,
+    Router,
+};
+use colored::Colorize;
+use futures::{stream, Stream};
+use itertools::Itertools;
+use log::{error, info, trace, warn};
+use notify::{EventKind, RecursiveMode, Watcher};
+use overlay::Failure;
+use parking_lot::RwLock;
+use tokio::{
+    net::TcpListener,
+    sync::{broadcast, mpsc},
+    time,
+};
+
+use crate::{
+    error::Result,
+    mkentry::Publishing,
+    proj::{BuildState, MoxideProj},
+    theme::Theme,
+    vfs::MemoryFs,
+};
+
+mod overlay;
+
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+const LIVE_RELOAD_PATH: &str = "/__moxide/livereload";
+
+/// Script injected into every served HTML page, reloading it once the site is rebuilt.
+const LIVE_RELOAD_SCRIPT: &str = r#"<script>
+new EventSource("/__moxide/livereload").addEventListener("reload", () => location.reload());
+</script>"#;
+
+/// Development server building the project, serving its output and rebuilding on change.
+///
+/// Output is kept in [`MemoryFs`] instead of the output directory, and only entries affected
+/// by a change are rendered again. Build failures are shown as an overlay on served pages.
+pub(crate) struct DevServer {
+    manifest: PathBuf,
+    addr: SocketAddr,
+    state: Arc<ServeState>,
+}
+
+struct ServeState {
+    memory: Arc<MemoryFs>,
+    failures: RwLock<Vec<Failure>>,
+    reload: broadcast::Sender<()>,
+}
+
+impl ServeState {
+    fn fail_with(&self, failures: Vec<Failure>) {
+        *self.failures.write() = failures;
+    }
+}
+
+impl DevServer {
+    pub(crate) fn new<P>(manifest: P, addr: SocketAddr) -> DevServer
+    where
+        P: AsRef<Path>,
+    {
+        // Watcher reports absolute paths, so keep everything absolute for comparing.
+        let manifest = manifest.as_ref();
+        let manifest = std::path::absolute(manifest).unwrap_or(manifest.to_path_buf());
+
+        DevServer {
+            manifest,
+            addr,
+            state: Arc::new(ServeState {
+                memory: Arc::new(MemoryFs::default()),
+                failures: RwLock::new(Vec::new()),
+                reload: broadcast::channel(16).0,
+            }),
+        }
+    }
+
+    pub(crate) async fn run(self) -> Result<()> {
+        let proj = Self::load(&self.manifest)?;
+        let built = proj.build_into(self.state.memory.clone()).await?;
+        self.state.fail_with(Self::failures_of(&built));
+
+        let (changed_tx, changed_rx) = mpsc::unbounded_channel();
+        let watched = self.watched(&proj);
+        let mut watcher =
+            notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+                match result {
+                    // Reading sources while building also emits events, only changes matter.
+                    Ok(event)
+                        if matches!(
+                            event.kind,
+                            EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                        ) =>
+                    {
+                        let paths = event
+                            .paths
+                            .into_iter()
+                            .filter(|it| watched.iter().any(|watched| it.starts_with(watched)))
+                            .collect_vec();
+                        if !paths.is_empty() {
+                            let _ = changed_tx.send(paths);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => error!("Failed watching project: {e}"),
+                }
+            })?;
+
+        // Whole project is watched, so directories created while serving count as well.
+        trace!(
+            "Watching {}",
+            proj.path_base().to_str().unwrap().underline()
+        );
+        watcher.watch(proj.path_base(), RecursiveMode::Recursive)?;
+
+        tokio::spawn(Self::rebuild_on_change(
+            self.manifest.clone(),
+            self.state.clone(),
+            built,
+            changed_rx,
+        ));
+
+        let app = Router::new()
+            .route(LIVE_RELOAD_PATH, get(live_reload))
+            .fallback(serve_file)
+            .with_state(self.state.clone());
+
+        let listener = TcpListener::bind(self.addr).await?;
+        info!(
+            "Serving at {}",
+            format!("http://{}", listener.local_addr()?)
+                .bold()
+                .underline()
+        );
+        axum::serve(listener, app).await?;
+
+        Ok(())
+    }
+
+    /// Load project for previewing, unpublished entries included.
+    fn load(manifest: &Path) -> Result<MoxideProj> {
+        let mut proj = MoxideProj::try_new(manifest)?;
+        proj.set_publishing(Publishing::all());
+        Ok(proj)
+    }
+
+    /// Paths to watch: `src/`, `manifest.toml` and the themes directory, even if they
+    /// don't exist yet.
+    ///
+    /// Whole `themes/` is watched since manifest may switch to another theme while serving.
+    fn watched(&self, proj: &MoxideProj) -> Vec<PathBuf> {
+        vec![
+            proj.path_src(),
+            self.manifest.clone(),
+            Theme::path_themes(proj.path_base()),
+        ]
+    }
+
+    async fn rebuild_on_change(
+        manifest: PathBuf,
+        state: Arc<ServeState>,
+        mut built: BuildState,
+        mut changed: mpsc::UnboundedReceiver<Vec<PathBuf>>,
+    ) {
+        while let Some(mut paths) = changed.recv().await {
+            // Editors usually touch several files at once, gather them into one rebuild.
+            while let Ok(Some(more)) = time::timeout(DEBOUNCE, changed.recv()).await {
+                paths.extend(more);
+            }
+
+            for path in paths.iter().unique() {
+                trace!("Changed {}", path.to_str().unwrap().underline());
+            }
+
+            // Manifest itself may be changed, so reload the whole project.
+            let rebuilt = match Self::load(&manifest) {
+                Ok(proj) => {
+                    let themes = Theme::path_themes(proj.path_base());
+                    let everything = paths
+                        .iter()
+                        .any(|it| *it == manifest || it.starts_with(&themes));
+
+                    if everything {
+                        Self::rebuild_all(&proj, &state.memory)
+                            .await
+                            .map(|it| built = it)
+                    } else {
+                        proj.rebuild_into(state.memory.clone(), &mut built, &paths)
+                            .await
+                    }
+                }
+                Err(e) => Err(e),
+            };
+
+            match rebuilt {
+                Ok(()) => {
+                    info!("Rebuilt project, reloading connected pages.");
+                    state.fail_with(Self::failures_of(&built));
+                }
+                Err(e) => {
+                    error!("Failed rebuilding project: {e}");
+                    state.fail_with(vec![Failure {
+                        path: manifest.clone(),
+                        message: e.to_string(),
+                        snippet: e.snippet(false),
+                    }]);
+                }
+            }
+
+            let _ = state.reload.send(());
+        }
+    }
+
+    /// Render the whole project aside, then swap it in, so pages stay available meanwhile.
+    async fn rebuild_all(proj: &MoxideProj, memory: &MemoryFs) -> Result<BuildState> {
+        let fresh = Arc::new(MemoryFs::default());
+        let built = proj.build_into(fresh.clone()).await?;
+        memory.replace_with(&fresh);
+
+        Ok(built)
+    }
+
+    fn failures_of(built: &BuildState) -> Vec<Failure> {
+        built
+            .failed
+            .iter()
+            .inspect(|(path, failure)| {
+                warn!(
+                    "Failed building {}: {failure}",
+                    path.to_str().unwrap().underline()
+                )
+            })
+            .map(|(path, failure)| Failure {
+                path: path.clone(),
+                message: failure.to_string(),
+                snippet: failure.cause.snippet(false),
+            })
+            .collect()
+    }
+}
+
+async fn live_reload(
+    State(state): State<Arc<ServeState>>,
+) -> Sse<impl Stream<Item = std::result::Result<Event, Infallible>>> {
+    let events = stream::unfold(state.reload.subscribe(), |mut rx| async move {
+        match rx.recv().await {
+            Ok(()) | Err(broadcast::error::RecvError::Lagged(_)) => {
+                Some((Ok(Event::default().event("reload").data("")), rx))
+            }
+            Err(broadcast::error::RecvError::Closed) => None,
+        }
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+async fn serve_file(State(state): State<Arc<ServeState>>, uri: Uri) -> Response {
+    let found = resolve(uri.path()).and_then(|path| {
+        let index = path.join("index.html");
+        [(path, false), (index, true)]
+            .into_iter()
+            .find_map(|(it, dir)| state.memory.read(&it).map(|content| (it, dir, content)))
+    });
+
+    let Some((path, dir, content)) = found else {
+        // Page of failed entry is gone, show what's wrong instead of nothing.
+        return match state.failures.read().as_slice() {
+            [] => StatusCode::NOT_FOUND.into_response(),
+            failures => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Html(inject(
+                    "<!DOCTYPE html><html><body></body></html>",
+                    failures,
+                )),
+            )
+                .into_response(),
+        };
+    };
+
+    // Relative links of a page resolve against its directory, which needs a trailing `/`.
+    if dir && !uri.path().ends_with('/') {
+        let location = match uri.query() {
+            Some(query) => format!("{}/?{query}", uri.path()),
+            None => format!("{}/", uri.path()),
+        };
+        return (
+            StatusCode::MOVED_PERMANENTLY,
+            [(header::LOCATION, location)],
+        )
+            .into_response();
+    }
+
+    let mime = mime_guess::from_path(&path).first_or_octet_stream();
+    let body = if mime == mime_guess::mime::TEXT_HTML {
+        Body::from(inject(
+            &String::from_utf8_lossy(&content),
+            &state.failures.read(),
+        ))
+    } else {
+        Body::from(content.to_vec())
+    };
+
+    ([(header::CONTENT_TYPE, mime.to_string())], body).into_response()
+}
+
+/// Map request path onto path relative to output root, rejecting anything escaping it.
+fn resolve(path: &str) -> Option<PathBuf> {
+    let decoded = urlencoding::decode(path).ok()?;
+
+    let mut resolved = PathBuf::new();
+    for component in Path::new(decoded.as_ref()).components() {
+        match component {
+            Component::Normal(it) => resolved.push(it),
+            Component::RootDir | Component::CurDir => {}
+            Component::ParentDir | Component::Prefix(_) => return None,
+        }
+    }
+
+    Some(resolved)
+}
+
+/// Inject live reload script, and overlay of `failures` if any, into served HTML.
+fn inject(html: &str, failures: &[Failure]) -> String {
+    let injected = if failures.is_empty() {
+        LIVE_RELOAD_SCRIPT.to_owned()
+    } else {
+        [&overlay::render(failures), LIVE_RELOAD_SCRIPT].concat()
+    };
+
+    match html.rfind("</body>") {
+        Some(index) => [&html[..index], &injected, &html[index..]].concat(),
+        None => [html, &injected].concat(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body;
+
+    use crate::vfs::Vfs;
+
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        assert_eq!(resolve("/"), Some(PathBuf::new()));
+        assert_eq!(
+            resolve("/2024/03/hello%20world/"),
+            Some(PathBuf::from("2024/03/hello world"))
+        );
+        assert_eq!(resolve("/./a/b.png"), Some(PathBuf::from("a/b.png")));
+        assert_eq!(resolve("/a/../../secret"), None);
+    }
+
+    #[test]
+    fn test_inject() {
+        let html = inject("<html><body><p>Hi</p></body></html>", &[]);
+        assert!(html.ends_with(&format!("<p>Hi</p>{LIVE_RELOAD_SCRIPT}</body></html>")));
+        assert_eq!(
+            inject("<p>Hi</p>", &[]),
+            format!("<p>Hi</p>{LIVE_RELOAD_SCRIPT}")
+        );
+
+        let failures = [Failure {
+            path: PathBuf::from("src/post/index.md"),
+            message: "invalid meta".to_owned(),
+            snippet: Some("1 | title =".to_owned()),
+        }];
+        let html = inject("<body></body>", &failures);
+        assert!(html.contains(r#"id="moxide-error-overlay""#));
+        assert!(html.ends_with(&format!("{LIVE_RELOAD_SCRIPT}</body>")));
+    }
+
+    #[tokio::test]
+    async fn test_serve_file() {
+        let memory = Arc::new(MemoryFs::default());
+        let bundle = Path::new("2024/03/bundle");
+        memory
+            .write(&bundle.join("index.html"), b"<body></body>".to_vec())
+            .await
+            .unwrap();
+        memory
+            .write(&bundle.join("assets/fig.png"), b"png".to_vec())
+            .await
+            .unwrap();
+        let state = Arc::new(ServeState {
+            memory,
+            failures: RwLock::new(Vec::new()),
+            reload: broadcast::channel(1).0,
+        });
+        let get = |uri: &'static str| serve_file(State(state.clone()), Uri::from_static(uri));
+
+        let response = get("/2024/03/bundle?q=1").await;
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(response.headers()[header::LOCATION], "/2024/03/bundle/?q=1");
+
+        let response = get("/2024/03/bundle/").await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let html = body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(String::from_utf8_lossy(&html).contains(LIVE_RELOAD_PATH));
+
+        let response = get("/2024/03/bundle/assets/fig.png").await;
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "image/png");
+
+        assert_eq!(get("/missing").await.status(), StatusCode::NOT_FOUND);
+    }
+}
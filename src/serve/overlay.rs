@@ -0,0 +1,92 @@
+use std::{fmt::Write, fs, path::PathBuf};
+
+use itertools::Itertools;
+
+use crate::render::escape_xml;
+
+/// Lines of source shown when the error doesn't tell where it happened.
+const FALLBACK_LINES: usize = 12;
+
+/// Build failure shown in the browser while serving.
+pub(super) struct Failure {
+    pub(super) path: PathBuf,
+    pub(super) message: String,
+
+    /// Annotated source snippet, if the error knows where it happened.
+    pub(super) snippet: Option<String>,
+}
+
+/// Render `failures` as an overlay covering the page.
+pub(super) fn render(failures: &[Failure]) -> String {
+    let mut overlay = String::from(
+        r#"<div id="moxide-error-overlay" style="position:fixed;inset:0;z-index:2147483647;overflow:auto;padding:2em;background:rgba(24,24,27,.96);color:#f4f4f5;font:14px/1.5 ui-monospace,monospace">"#,
+    );
+    let _ = write!(
+        overlay,
+        r#"<h2 style="color:#f87171;margin-top:0">Failed building {} file(s)</h2>"#,
+        failures.len()
+    );
+
+    for failure in failures {
+        let _ = write!(
+            overlay,
+            r#"<section style="margin-bottom:2em"><div style="color:#a1a1aa">{}</div><div style="color:#fca5a5;white-space:pre-wrap;margin:.5em 0">{}</div><pre style="background:#09090b;padding:1em;overflow:auto">{}</pre></section>"#,
+            escape_xml(&failure.path.to_string_lossy()),
+            escape_xml(&failure.message),
+            failure
+                .snippet
+                .as_deref()
+                .map(escape_xml)
+                .unwrap_or_else(|| source_lines(failure)),
+        );
+    }
+
+    overlay.push_str("</div>");
+    overlay
+}
+
+/// Numbered leading lines of the failed file, where front matter lives.
+fn source_lines(failure: &Failure) -> String {
+    let Ok(source) = fs::read_to_string(&failure.path) else {
+        return String::new();
+    };
+
+    source
+        .lines()
+        .take(FALLBACK_LINES)
+        .enumerate()
+        .map(|(number, line)| format!("{:>4} | {}", number + 1, escape_xml(line)))
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = dir.path().join("index.md");
+        fs::write(&index, "+++\ntitle = <Hi>\n+++\n").unwrap();
+
+        let failures = [
+            Failure {
+                path: PathBuf::from("src/a&b/index.md"),
+                message: "expected `<`".to_owned(),
+                snippet: Some("1 | <p>".to_owned()),
+            },
+            Failure {
+                path: index,
+                message: "invalid meta".to_owned(),
+                snippet: None,
+            },
+        ];
+
+        let overlay = render(&failures);
+        assert!(overlay.contains("Failed building 2 file(s)"));
+        assert!(overlay.contains("src/a&amp;b/index.md"));
+        assert!(overlay.contains("expected `&lt;`"));
+        assert!(overlay.contains("1 | &lt;p&gt;"));
+        assert!(overlay.contains("   2 | title = &lt;Hi&gt;"));
+    }
+}
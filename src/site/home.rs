@@ -0,0 +1,96 @@
+use serde::Serialize;
+
+use crate::manifest::HomeConfig;
+
+use super::{taxonomy, Section, SitePage};
+
+/// Home page at the root of site, listing recent pages.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct Home {
+    pub(crate) url: String,
+
+    /// Body of `src/_index.md` rendered as HTML.
+    pub(crate) content: String,
+
+    pub(crate) paginate_by: Option<usize>,
+
+    /// Recent pages picked by home config, newest first.
+    pub(crate) pages: Vec<SitePage>,
+}
+
+impl Home {
+    /// Pick pages for home out of `pages`, which are newest first.
+    pub(crate) fn new(config: &HomeConfig, root: &Section, pages: &[SitePage]) -> Home {
+        let section = config.section.as_deref().map(|it| it.trim_matches('/'));
+        let tag = config
+            .tag
+            .as_deref()
+            .and_then(taxonomy::normalize)
+            .map(|it| it.to_lowercase());
+
+        let pages = pages
+            .iter()
+            .filter(|page| section.is_none_or(|section| Section::is_within(&page.section, section)))
+            .filter(|page| {
+                tag.as_ref()
+                    .is_none_or(|tag| page.tags.iter().any(|it| it.to_lowercase() == *tag))
+            })
+            .take(config.count.unwrap_or(usize::MAX))
+            .cloned()
+            .collect();
+
+        Home {
+            url: root.url.clone(),
+            content: root.content.clone(),
+            paginate_by: root.paginate_by,
+            pages,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, path::PathBuf};
+
+    use chrono::Local;
+
+    use crate::{
+        mkentry::{SectionIndex, Status},
+        render::Markdown,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_tag() {
+        let page = |title: &str, tags: &[&str]| SitePage {
+            index: PathBuf::new(),
+            title: title.to_owned(),
+            url: String::new(),
+            date: Local::now().fixed_offset(),
+            updated: None,
+            tags: tags.iter().map(|it| it.to_string()).collect(),
+            taxonomies: BTreeMap::new(),
+            status: Status::Published,
+            description: String::new(),
+            section: String::new(),
+        };
+        let pages = [page("a", &["Rust Lang"]), page("b", &["Go"])];
+        let config = HomeConfig {
+            tag: Some(" rust \tlang ".to_owned()),
+            ..HomeConfig::default()
+        };
+
+        let root = Section::new(
+            String::new(),
+            "Moxide".to_owned(),
+            "/".to_owned(),
+            &SectionIndex::default(),
+            &Markdown::default(),
+        );
+
+        let home = Home::new(&config, &root, &pages);
+        assert_eq!(home.pages.len(), 1);
+        assert_eq!(home.pages[0].title, "a");
+    }
+}
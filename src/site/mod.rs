@@ -0,0 +1,320 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    iter, mem,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, FixedOffset, Local};
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{
+    manifest::Manifest,
+    mkentry::{MarkdownEntry, SectionIndex, Status},
+    permalink::{self, Permalink},
+    render::{feed_links, Markdown, HIGHLIGHT_CSS},
+};
+
+pub(crate) use home::Home;
+pub(crate) use paginator::paginate;
+pub(crate) use section::{Link, Section};
+pub(crate) use taxonomy::{Taxonomy, Term};
+
+mod home;
+mod paginator;
+mod section;
+mod taxonomy;
+
+/// Every entry of the project, collected before any of them is rendered.
+///
+/// Renders read it through [`crate::mkentry::MarkdownEntryContext::site`], and themes
+/// as `site`, so pages may list and link other pages.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub(crate) struct Site {
+    pub(crate) title: String,
+    pub(crate) description: String,
+
+    /// Address site is published at, without trailing slash.
+    pub(crate) base_url: String,
+
+    /// Links to feeds of the whole site.
+    pub(crate) feeds: Vec<Link>,
+
+    /// URLs of stylesheets generated for site, such as one of highlighted code.
+    pub(crate) stylesheets: Vec<String>,
+
+    /// Whether `search.js` and its index are published.
+    pub(crate) search: bool,
+
+    /// Every built page, newest first.
+    pub(crate) pages: Vec<SitePage>,
+
+    /// Every section keyed by its directory relative to `src`, the root one keyed by empty path.
+    pub(crate) sections: BTreeMap<String, Section>,
+
+    pub(crate) home: Home,
+
+    /// Every taxonomy declared in manifest, keyed by its name.
+    pub(crate) taxonomies: BTreeMap<String, Taxonomy>,
+}
+
+impl Site {
+    /// Collect `pages` into site, along with every section holding them.
+    ///
+    /// `indexes` holds parsed `_index.md` of sections keyed by section path.
+    pub(crate) fn new(
+        manifest: &Manifest,
+        permalink: &Permalink,
+        markdown: &Markdown,
+        pages: Vec<SitePage>,
+        mut indexes: BTreeMap<String, SectionIndex>,
+    ) -> Site {
+        let pages = pages
+            .into_iter()
+            .sorted_by(|a, b| b.date.cmp(&a.date).then_with(|| a.title.cmp(&b.title)))
+            .collect_vec();
+
+        // Every ancestor of a section is a section too, up to the root one.
+        let paths = indexes
+            .keys()
+            .map(String::as_str)
+            .chain(pages.iter().map(|it| it.section.as_str()))
+            .chain(iter::once(""))
+            .flat_map(|it| iter::successors(Some(it), |it| Section::parent_of(it)))
+            .map(str::to_owned)
+            .collect::<BTreeSet<_>>();
+
+        let mut sections = paths
+            .into_iter()
+            .map(|path| {
+                let index = indexes.remove(&path).unwrap_or_default();
+                let title =
+                    index
+                        .meta
+                        .title
+                        .clone()
+                        .unwrap_or_else(|| match path.rsplit_once('/') {
+                            _ if path.is_empty() => manifest.site.clone(),
+                            Some((_, name)) => decode(name),
+                            None => decode(&path),
+                        });
+                let url = permalink::url(Path::new(&permalink.section(&path)));
+
+                let mut section = Section::new(path.clone(), title, url, &index, markdown);
+                section.paginate_by = section.paginate_by.or(manifest.paginate_by);
+                if index.meta.feed {
+                    section.feeds = feed_links(manifest, &section.url);
+                }
+
+                (path, section)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        for page in &pages {
+            if let Some(section) = sections.get_mut(&page.section) {
+                section.pages.push(page.clone());
+            }
+        }
+
+        let links = sections
+            .iter()
+            .map(|(path, section)| (path.clone(), section.link()))
+            .collect::<BTreeMap<_, _>>();
+
+        for (path, section) in sections.iter_mut() {
+            let mut pages = mem::take(&mut section.pages);
+            pages.sort_by(|a, b| section.compare(a, b));
+            section.pages = pages;
+
+            section.breadcrumbs =
+                iter::successors(Section::parent_of(path), |it| Section::parent_of(it))
+                    .map(|it| links[it].clone())
+                    .collect_vec();
+            section.breadcrumbs.reverse();
+
+            section.subsections = links
+                .iter()
+                .filter(|(it, _)| Section::parent_of(it) == Some(path))
+                .map(|(_, link)| link.clone())
+                .collect();
+        }
+
+        let taxonomies = manifest
+            .taxonomies
+            .iter()
+            .map(|it| {
+                let url = permalink::url(Path::new(&permalink.taxonomy(&it.name, None)));
+                (it.name.clone(), Taxonomy::new(it, url, &pages, manifest))
+            })
+            .collect();
+
+        let home = Home::new(&manifest.home, &sections[""], &pages);
+
+        Site {
+            title: manifest.site.clone(),
+            description: manifest.description.clone(),
+            base_url: manifest.base_url.trim_end_matches('/').to_owned(),
+            feeds: feed_links(manifest, "/"),
+            stylesheets: markdown
+                .stylesheet()
+                .map(|_| format!("/{HIGHLIGHT_CSS}"))
+                .into_iter()
+                .collect(),
+            search: manifest.search.enabled,
+            home,
+            pages,
+            sections,
+            taxonomies,
+        }
+    }
+
+    /// Summary of page built from `index`.
+    pub(crate) fn page(&self, index: &Path) -> Option<&SitePage> {
+        self.pages.iter().find(|it| it.index == index)
+    }
+}
+
+/// Section path of entry at `index`, its closest ancestor directory not being an entry itself.
+pub(crate) fn section_of(src: &Path, index: &Path) -> String {
+    let dir = index
+        .parent()
+        .into_iter()
+        .flat_map(Path::ancestors)
+        .skip(1)
+        .find(|it| *it == src || !it.join("index.md").is_file())
+        .unwrap_or(src);
+
+    section_path(src, dir)
+}
+
+/// Section path of `dir`, relative to `src` and joined by `/`.
+pub(crate) fn section_path(src: &Path, dir: &Path) -> String {
+    dir.strip_prefix(src)
+        .unwrap_or(Path::new(""))
+        .components()
+        .map(|it| it.as_os_str().to_string_lossy())
+        .join("/")
+}
+
+/// Directory name as readable title, undoing URL encoding of older projects.
+fn decode(name: &str) -> String {
+    urlencoding::decode(name)
+        .map(|it| it.into_owned())
+        .unwrap_or_else(|_| name.to_owned())
+}
+
+/// Summary of a single page, enough for listing and linking it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub(crate) struct SitePage {
+    /// Index markdown of page.
+    #[serde(skip)]
+    pub(crate) index: PathBuf,
+
+    pub(crate) title: String,
+    pub(crate) url: String,
+    pub(crate) date: DateTime<FixedOffset>,
+    pub(crate) updated: Option<DateTime<FixedOffset>>,
+    pub(crate) tags: Vec<String>,
+
+    /// Links to terms of every taxonomy used by page, keyed by taxonomy name.
+    pub(crate) taxonomies: BTreeMap<String, Vec<Link>>,
+
+    pub(crate) status: Status,
+
+    /// Description rendered as HTML.
+    pub(crate) description: String,
+
+    /// Directory of section holding the page, relative to `src`.
+    pub(crate) section: String,
+}
+
+impl SitePage {
+    /// Summarize `entry` of `index` in `section`, published at `output`.
+    pub(crate) fn new(
+        manifest: &Manifest,
+        permalink: &Permalink,
+        markdown: &Markdown,
+        index: &Path,
+        entry: &MarkdownEntry,
+        output: &Path,
+        section: String,
+    ) -> SitePage {
+        SitePage {
+            index: index.to_path_buf(),
+            title: entry.meta.title.clone(),
+            url: permalink::url(output),
+            date: entry.meta.date,
+            updated: entry.meta.updated,
+            tags: entry
+                .meta
+                .tag
+                .iter()
+                .flat_map(|it| taxonomy::normalize(it))
+                .sorted()
+                .dedup()
+                .collect(),
+            taxonomies: taxonomy::links_of(&entry.meta, &manifest.taxonomies, permalink),
+            status: entry.meta.status_at(Local::now()),
+            description: markdown
+                .render_with(&entry.description, &entry.meta.markdown)
+                .0,
+            section,
+        }
+    }
+
+    /// Date page last changed, its publication date unless updated since.
+    pub(crate) fn modified(&self) -> DateTime<FixedOffset> {
+        self.updated.unwrap_or(self.date).max(self.date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::permalink::SlugStyle;
+
+    use super::*;
+
+    #[test]
+    fn test_sections() {
+        let manifest: Manifest = "site = \"Moxide\"".to_owned().try_into().unwrap();
+        let permalink = Permalink::try_new(&manifest.permalink, SlugStyle::Ascii).unwrap();
+        let page = |title: &str, section: &str| SitePage {
+            index: PathBuf::new(),
+            title: title.to_owned(),
+            url: String::new(),
+            date: Local::now().fixed_offset(),
+            updated: None,
+            tags: Vec::new(),
+            taxonomies: BTreeMap::new(),
+            status: Status::Published,
+            description: String::new(),
+            section: section.to_owned(),
+        };
+
+        let site = Site::new(
+            &manifest,
+            &permalink,
+            &Markdown::default(),
+            vec![
+                page("b", "blog/rust"),
+                page("a", "blog/rust"),
+                page("c", ""),
+            ],
+            BTreeMap::new(),
+        );
+
+        assert_eq!(
+            site.sections.keys().collect_vec(),
+            ["", "blog", "blog/rust"]
+        );
+
+        let rust = &site.sections["blog/rust"];
+        assert_eq!(rust.url, "/blog/rust/");
+        assert_eq!(
+            rust.breadcrumbs.iter().map(|it| &it.title).collect_vec(),
+            ["Moxide", "blog"]
+        );
+        assert_eq!(rust.pages.len(), 2);
+        assert_eq!(site.sections["blog"].subsections[0].url, "/blog/rust/");
+    }
+}
@@ -0,0 +1,88 @@
+use serde::Serialize;
+
+use super::SitePage;
+
+/// A single page of paginated list, exposed to templates as `paginator`.
+#[derive(Debug, Serialize)]
+pub(crate) struct Paginator<'a> {
+    /// Pages listed on this page.
+    pub(crate) pages: &'a [SitePage],
+
+    /// Number of this page, starting from 1.
+    pub(crate) current: usize,
+
+    /// Number of pages of the list.
+    pub(crate) total: usize,
+
+    /// Number of pages listed across the whole list.
+    pub(crate) items: usize,
+
+    pub(crate) url: String,
+    pub(crate) first: String,
+    pub(crate) last: String,
+    pub(crate) prev: Option<String>,
+    pub(crate) next: Option<String>,
+}
+
+/// Split `pages` of list at `url` by `size`, into a single page if `None` or zero.
+///
+/// First page lives at `url` itself, and others at `<url>page/<n>/`.
+pub(crate) fn paginate<'a>(
+    pages: &'a [SitePage],
+    size: Option<usize>,
+    url: &str,
+) -> Vec<Paginator<'a>> {
+    let size = size.filter(|it| *it > 0).unwrap_or(pages.len().max(1));
+    let chunks = pages.chunks(size).collect::<Vec<_>>();
+    let total = chunks.len().max(1);
+
+    let url_of = |number: usize| {
+        if number == 1 {
+            url.to_owned()
+        } else {
+            format!("{url}page/{number}/")
+        }
+    };
+
+    (1..=total)
+        .map(|current| Paginator {
+            pages: chunks.get(current - 1).copied().unwrap_or_default(),
+            current,
+            total,
+            items: pages.len(),
+            url: url_of(current),
+            first: url_of(1),
+            last: url_of(total),
+            prev: (current > 1).then(|| url_of(current - 1)),
+            next: (current < total).then(|| url_of(current + 1)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paginate() {
+        assert_eq!(paginate(&[], Some(10), "/blog/").len(), 1);
+
+        let pages = (0..5)
+            .map(|it| SitePage {
+                title: it.to_string(),
+                ..SitePage::default()
+            })
+            .collect::<Vec<_>>();
+
+        let paginators = paginate(&pages, Some(2), "/blog/");
+        assert_eq!(paginators.len(), 3);
+        assert_eq!(paginators[0].url, "/blog/");
+        assert_eq!(paginators[0].prev, None);
+        assert_eq!(paginators[0].next.as_deref(), Some("/blog/page/2/"));
+        assert_eq!(paginators[2].pages.len(), 1);
+        assert_eq!(paginators[2].prev.as_deref(), Some("/blog/page/2/"));
+        assert_eq!(paginators[2].next, None);
+
+        assert_eq!(paginate(&pages, None, "/blog/").len(), 1);
+    }
+}
@@ -0,0 +1,107 @@
+use std::cmp::Ordering;
+
+use serde::Serialize;
+
+use crate::{
+    mkentry::{SectionIndex, SortBy},
+    render::Markdown,
+};
+
+use super::SitePage;
+
+/// Directory under `src` grouping entries, listed by a generated page.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct Section {
+    /// Directory relative to `src`, empty for the root section.
+    pub(crate) path: String,
+
+    pub(crate) title: String,
+    pub(crate) url: String,
+
+    /// Body of `_index.md` rendered as HTML.
+    pub(crate) content: String,
+
+    pub(crate) sort_by: SortBy,
+    pub(crate) reverse: bool,
+    pub(crate) paginate_by: Option<usize>,
+
+    /// Links to every ancestor section, root first.
+    pub(crate) breadcrumbs: Vec<Link>,
+
+    /// Links to child sections.
+    pub(crate) subsections: Vec<Link>,
+
+    /// Links to feeds of pages under section, empty unless asked by `_index.md`.
+    pub(crate) feeds: Vec<Link>,
+
+    /// Pages directly under this section, in order of `sort_by`.
+    pub(crate) pages: Vec<SitePage>,
+}
+
+/// Title and URL of a page or section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub(crate) struct Link {
+    pub(crate) title: String,
+    pub(crate) url: String,
+}
+
+impl Section {
+    pub(crate) fn new(
+        path: String,
+        title: String,
+        url: String,
+        index: &SectionIndex,
+        markdown: &Markdown,
+    ) -> Section {
+        Section {
+            path,
+            title,
+            url,
+            content: markdown.render(&index.content),
+            sort_by: index.meta.sort_by,
+            reverse: index.meta.reverse,
+            paginate_by: index.meta.paginate_by,
+            breadcrumbs: Vec::new(),
+            subsections: Vec::new(),
+            feeds: Vec::new(),
+            pages: Vec::new(),
+        }
+    }
+
+    /// Parent section path, `None` for the root section.
+    pub(crate) fn parent_of(path: &str) -> Option<&str> {
+        if path.is_empty() {
+            None
+        } else {
+            Some(path.rsplit_once('/').map(|(it, _)| it).unwrap_or(""))
+        }
+    }
+
+    /// Whether section at `path` is the one at `ancestor` or nested inside it.
+    pub(crate) fn is_within(path: &str, ancestor: &str) -> bool {
+        ancestor.is_empty()
+            || path
+                .strip_prefix(ancestor)
+                .is_some_and(|it| it.is_empty() || it.starts_with('/'))
+    }
+
+    pub(crate) fn link(&self) -> Link {
+        Link {
+            title: self.title.clone(),
+            url: self.url.clone(),
+        }
+    }
+
+    pub(crate) fn compare(&self, a: &SitePage, b: &SitePage) -> Ordering {
+        let ordering = match self.sort_by {
+            SortBy::Date => b.date.cmp(&a.date).then_with(|| a.title.cmp(&b.title)),
+            SortBy::Title => a.title.cmp(&b.title).then_with(|| b.date.cmp(&a.date)),
+        };
+
+        if self.reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
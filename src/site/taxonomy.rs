@@ -0,0 +1,160 @@
+use std::{collections::BTreeMap, path::Path};
+
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::{
+    manifest::{Manifest, TaxonomyConfig},
+    mkentry::MarkdownMeta,
+    permalink::{self, Permalink},
+    render,
+};
+
+use super::{Link, SitePage};
+
+/// Taxonomy along with every term used by pages, listed by generated pages.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct Taxonomy {
+    pub(crate) name: String,
+    pub(crate) url: String,
+
+    /// Terms ordered by name.
+    pub(crate) terms: Vec<Term>,
+
+    #[serde(skip)]
+    pub(crate) template: String,
+
+    #[serde(skip)]
+    pub(crate) term_template: String,
+
+    /// Pages listed on every page of a term.
+    pub(crate) paginate_by: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub(crate) struct Term {
+    pub(crate) name: String,
+    pub(crate) url: String,
+
+    /// Links to feeds of term, empty unless asked by taxonomy.
+    pub(crate) feeds: Vec<Link>,
+
+    /// Pages using term, newest first.
+    pub(crate) pages: Vec<SitePage>,
+}
+
+impl Taxonomy {
+    /// Collect terms of taxonomy from `pages`, which are newest first.
+    ///
+    /// Terms sharing a URL are the same one, such as those differing in case or
+    /// punctuation only, named as the newest page writes it.
+    pub(crate) fn new(
+        config: &TaxonomyConfig,
+        url: String,
+        pages: &[SitePage],
+        manifest: &Manifest,
+    ) -> Taxonomy {
+        let mut terms = BTreeMap::<String, Term>::new();
+
+        for page in pages {
+            for link in page.taxonomies.get(&config.name).into_iter().flatten() {
+                terms
+                    .entry(link.url.clone())
+                    .or_insert_with(|| Term {
+                        name: link.title.clone(),
+                        url: link.url.clone(),
+                        feeds: if config.feed {
+                            render::feed_links(manifest, &link.url)
+                        } else {
+                            Vec::new()
+                        },
+                        pages: Vec::new(),
+                    })
+                    .pages
+                    .push(page.clone());
+            }
+        }
+
+        Taxonomy {
+            name: config.name.clone(),
+            url,
+            terms: terms
+                .into_values()
+                .sorted_by_key(|it| it.name.to_lowercase())
+                .collect(),
+            template: config.template.clone(),
+            term_template: config.term_template.clone(),
+            paginate_by: config.paginate_by.or(manifest.paginate_by),
+        }
+    }
+}
+
+/// Collapse whitespace inside `term`, `None` if nothing is left.
+pub(crate) fn normalize(term: &str) -> Option<String> {
+    let term = term.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if term.is_empty() {
+        None
+    } else {
+        Some(term)
+    }
+}
+
+/// Links to terms of every taxonomy used by page with `meta`, keyed by taxonomy name.
+pub(crate) fn links_of(
+    meta: &MarkdownMeta,
+    taxonomies: &[TaxonomyConfig],
+    permalink: &Permalink,
+) -> BTreeMap<String, Vec<Link>> {
+    taxonomies
+        .iter()
+        .map(|taxonomy| {
+            // Terms sharing a URL are the same one.
+            let links = meta
+                .terms(&taxonomy.name)
+                .flat_map(normalize)
+                .map(|term| Link {
+                    url: permalink::url(Path::new(
+                        &permalink.taxonomy(&taxonomy.name, Some(&term)),
+                    )),
+                    title: term,
+                })
+                .sorted_by_key(|it| it.title.to_lowercase())
+                .unique_by(|it| it.url.clone())
+                .collect::<Vec<_>>();
+
+            (taxonomy.name.clone(), links)
+        })
+        .filter(|(_, links)| !links.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::permalink::SlugStyle;
+
+    use super::*;
+
+    #[test]
+    fn test_links_of() {
+        let permalink = Permalink::try_new("/:slug/", SlugStyle::Ascii).unwrap();
+        let meta = MarkdownMeta {
+            tag: ["  Rust \t Lang ", "rust-lang", " "]
+                .map(str::to_owned)
+                .into(),
+            taxonomies: [("authors".to_owned(), vec!["Ferris".to_owned()])].into(),
+            ..MarkdownMeta::default()
+        };
+        let taxonomies = [TaxonomyConfig::new("tags"), TaxonomyConfig::new("authors")];
+
+        let links = links_of(&meta, &taxonomies, &permalink);
+        assert_eq!(
+            links["tags"],
+            [Link {
+                title: "Rust Lang".to_owned(),
+                url: "/tags/rust-lang/".to_owned(),
+            }]
+        );
+        assert_eq!(links["authors"][0].url, "/authors/ferris/");
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, FixedOffset, Local};
+use colored::Colorize;
+use itertools::Itertools;
+use log::trace;
+use minijinja::Environment;
+use serde::Serialize;
+
+use crate::{
+    error::{Error, Result},
+    manifest::Manifest,
+    mkentry::{MarkdownMeta, Status},
+    render::TocEntry,
+    site::Link,
+};
+
+/// Layout templates shipped with Moxide, used when the theme doesn't override them.
+const BUILTIN: &[(&str, &str)] = &[
+    ("base.html", include_str!("default/base.html")),
+    ("index.html", include_str!("default/index.html")),
+    ("page.html", include_str!("default/page.html")),
+    ("list.html", include_str!("default/list.html")),
+    ("taxonomy.html", include_str!("default/taxonomy.html")),
+    ("term.html", include_str!("default/term.html")),
+];
+
+pub(crate) struct Theme {
+    env: Environment<'static>,
+}
+
+impl Theme {
+    /// Load theme `Manifest::theme` from `themes/<name>/` under project base.
+    ///
+    /// Templates missing from the theme directory fall back to the builtin ones,
+    /// and an empty theme name uses the builtin templates only.
+    pub(crate) fn try_new<P>(base: P, manifest: &Manifest) -> Result<Theme>
+    where
+        P: AsRef<Path>,
+    {
+        let dir = Self::path_theme(base, &manifest.theme);
+
+        if let Some(dir) = &dir {
+            if !dir.is_dir() {
+                return Err(Error::ThemeNotFound(manifest.theme.clone()));
+            }
+
+            trace!(
+                "Loading theme `{}` from {}",
+                manifest.theme.bold(),
+                dir.to_str().unwrap().bold().underline()
+            );
+        }
+
+        let mut env = Environment::new();
+        env.set_loader(move |name| {
+            if let Some(dir) = &dir {
+                match fs::read_to_string(dir.join(name)) {
+                    Ok(template) => return Ok(Some(template)),
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                    Err(e) => {
+                        return Err(minijinja::Error::new(
+                            minijinja::ErrorKind::InvalidOperation,
+                            format!("failed to load template `{name}`"),
+                        )
+                        .with_source(e))
+                    }
+                }
+            }
+
+            Ok(BUILTIN
+                .iter()
+                .find(|(builtin, _)| *builtin == name)
+                .map(|(_, template)| template.to_string()))
+        });
+
+        Ok(Theme { env })
+    }
+
+    /// Directory holding every theme of project.
+    pub(crate) fn path_themes<P>(base: P) -> PathBuf
+    where
+        P: AsRef<Path>,
+    {
+        base.as_ref().join("themes")
+    }
+
+    /// Directory of theme, `None` if using the builtin theme.
+    pub(crate) fn path_theme<P>(base: P, name: &str) -> Option<PathBuf>
+    where
+        P: AsRef<Path>,
+    {
+        if name.is_empty() {
+            None
+        } else {
+            Some(Self::path_themes(base).join(name))
+        }
+    }
+
+    /// Render template `name` with `ctx`.
+    pub(crate) fn render<S>(&self, name: &str, ctx: S) -> Result<String>
+    where
+        S: Serialize,
+    {
+        Ok(self.env.get_template(name)?.render(ctx)?)
+    }
+}
+
+/// Template context of a single rendered entry, exposed as `page`.
+#[derive(Debug, Serialize)]
+pub(crate) struct PageContext<'a> {
+    pub(crate) title: &'a str,
+    pub(crate) url: String,
+    pub(crate) date: &'a DateTime<FixedOffset>,
+    pub(crate) updated: Option<&'a DateTime<FixedOffset>>,
+    pub(crate) tags: Vec<&'a str>,
+    /// Links to terms of every taxonomy used by entry, keyed by taxonomy name.
+    pub(crate) taxonomies: BTreeMap<String, Vec<Link>>,
+    /// Publication state, unpublished entries show up in previews only.
+    pub(crate) status: Status,
+    pub(crate) description: String,
+    pub(crate) content: String,
+    /// Headings of content, nested by level.
+    pub(crate) toc: Vec<TocEntry>,
+    pub(crate) resources: Vec<ResourceContext>,
+}
+
+impl<'a> PageContext<'a> {
+    pub(crate) fn new(
+        meta: &'a MarkdownMeta,
+        url: String,
+        description: String,
+        content: String,
+    ) -> Self {
+        Self {
+            title: &meta.title,
+            url,
+            date: &meta.date,
+            updated: meta.updated.as_ref(),
+            tags: meta.tag.iter().map(String::as_str).sorted().collect(),
+            taxonomies: BTreeMap::new(),
+            status: meta.status_at(Local::now()),
+            description,
+            content,
+            toc: Vec::new(),
+            resources: Vec::new(),
+        }
+    }
+}
+
+/// Template context of a file published along with a bundle, exposed as `page.resources`.
+#[derive(Debug, Serialize)]
+pub(crate) struct ResourceContext {
+    /// File name of resource.
+    pub(crate) name: String,
+    /// Path relative to the rendered page, usable as link target directly.
+    pub(crate) path: String,
+    /// `image` for pictures, `file` for anything else.
+    pub(crate) kind: &'static str,
+    pub(crate) size: u64,
+}
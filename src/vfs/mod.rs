@@ -0,0 +1,227 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use parking_lot::{Mutex, RwLock};
+use tokio::fs;
+
+use crate::error::Result;
+
+/// File system which renders write their output into.
+///
+/// Every path is relative to the root of output.
+#[async_trait]
+pub(crate) trait Vfs: Send + Sync {
+    /// Write `content` into `path`, creating parent directories as needed.
+    async fn write(&self, path: &Path, content: Vec<u8>) -> Result<()>;
+
+    /// Copy `source` from disk into `path`, returning copied size.
+    async fn copy(&self, source: &Path, path: &Path) -> Result<u64>;
+
+    /// Remove `path` and everything under it, if exists.
+    async fn remove_all(&self, path: &Path) -> Result<()>;
+}
+
+/// Output written into a directory on disk.
+pub(crate) struct DiskFs {
+    root: PathBuf,
+}
+
+impl DiskFs {
+    pub(crate) fn new<P>(root: P) -> DiskFs
+    where
+        P: AsRef<Path>,
+    {
+        DiskFs {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+
+    async fn prepare(&self, path: &Path) -> Result<PathBuf> {
+        let target = self.root.join(path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        Ok(target)
+    }
+}
+
+#[async_trait]
+impl Vfs for DiskFs {
+    async fn write(&self, path: &Path, content: Vec<u8>) -> Result<()> {
+        let target = self.prepare(path).await?;
+        Ok(fs::write(target, content).await?)
+    }
+
+    async fn copy(&self, source: &Path, path: &Path) -> Result<u64> {
+        let target = self.prepare(path).await?;
+        Ok(fs::copy(source, target).await?)
+    }
+
+    async fn remove_all(&self, path: &Path) -> Result<()> {
+        let target = self.root.join(path);
+        match fs::metadata(&target).await {
+            Ok(meta) if meta.is_dir() => Ok(fs::remove_dir_all(target).await?),
+            Ok(_) => Ok(fs::remove_file(target).await?),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+/// Output kept in memory, served by the development server directly.
+#[derive(Default)]
+pub(crate) struct MemoryFs {
+    files: RwLock<HashMap<PathBuf, Arc<[u8]>>>,
+}
+
+impl MemoryFs {
+    pub(crate) fn read(&self, path: &Path) -> Option<Arc<[u8]>> {
+        self.files.read().get(path).cloned()
+    }
+
+    /// Take over every file of `other`, dropping current ones.
+    pub(crate) fn replace_with(&self, other: &MemoryFs) {
+        let files = std::mem::take(&mut *other.files.write());
+        *self.files.write() = files;
+    }
+}
+
+#[async_trait]
+impl Vfs for MemoryFs {
+    async fn write(&self, path: &Path, content: Vec<u8>) -> Result<()> {
+        self.files
+            .write()
+            .insert(path.to_path_buf(), content.into());
+        Ok(())
+    }
+
+    async fn copy(&self, source: &Path, path: &Path) -> Result<u64> {
+        let content = fs::read(source).await?;
+        let size = content.len() as u64;
+        self.write(path, content).await?;
+        Ok(size)
+    }
+
+    async fn remove_all(&self, path: &Path) -> Result<()> {
+        self.files.write().retain(|it, _| !it.starts_with(path));
+        Ok(())
+    }
+}
+
+/// Output written into `inner`, remembering every file written for removing them later.
+pub(crate) struct Recording {
+    inner: Arc<dyn Vfs>,
+    written: Mutex<HashSet<PathBuf>>,
+}
+
+impl Recording {
+    pub(crate) fn new(inner: Arc<dyn Vfs>) -> Recording {
+        Recording {
+            inner,
+            written: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Every file written so far and not removed since.
+    pub(crate) fn written(&self) -> HashSet<PathBuf> {
+        self.written.lock().clone()
+    }
+}
+
+#[async_trait]
+impl Vfs for Recording {
+    async fn write(&self, path: &Path, content: Vec<u8>) -> Result<()> {
+        self.written.lock().insert(path.to_path_buf());
+        self.inner.write(path, content).await
+    }
+
+    async fn copy(&self, source: &Path, path: &Path) -> Result<u64> {
+        self.written.lock().insert(path.to_path_buf());
+        self.inner.copy(source, path).await
+    }
+
+    async fn remove_all(&self, path: &Path) -> Result<()> {
+        self.written.lock().retain(|it| !it.starts_with(path));
+        self.inner.remove_all(path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_fs() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("fig.png");
+        std::fs::write(&source, "png").unwrap();
+
+        let memory = MemoryFs::default();
+        memory
+            .write(Path::new("docs/index.html"), b"docs".to_vec())
+            .await
+            .unwrap();
+        memory
+            .write(Path::new("docs2/index.html"), b"docs2".to_vec())
+            .await
+            .unwrap();
+        let size = memory
+            .copy(&source, Path::new("docs/fig.png"))
+            .await
+            .unwrap();
+        assert_eq!(size, 3);
+        assert_eq!(&*memory.read(Path::new("docs/fig.png")).unwrap(), b"png");
+
+        // Siblings sharing a prefix of name stay.
+        memory.remove_all(Path::new("docs")).await.unwrap();
+        assert!(memory.read(Path::new("docs/index.html")).is_none());
+        assert!(memory.read(Path::new("docs2/index.html")).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_disk_fs() {
+        let dir = tempfile::tempdir().unwrap();
+        let disk = DiskFs::new(dir.path());
+
+        disk.write(Path::new("a/b/index.html"), b"b".to_vec())
+            .await
+            .unwrap();
+        disk.write(Path::new("a/index.html"), b"a".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read(dir.path().join("a/b/index.html")).unwrap(),
+            b"b"
+        );
+
+        disk.remove_all(Path::new("a/index.html")).await.unwrap();
+        assert!(dir.path().join("a/b/index.html").is_file());
+        disk.remove_all(Path::new("a")).await.unwrap();
+        assert!(!dir.path().join("a").exists());
+        disk.remove_all(Path::new("missing")).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_recording() {
+        let memory = Arc::new(MemoryFs::default());
+        let recording = Recording::new(memory.clone());
+
+        for path in ["docs/index.html", "docs/assets/fig.png", "docs/a.txt"] {
+            recording.write(Path::new(path), Vec::new()).await.unwrap();
+        }
+        recording
+            .remove_all(Path::new("docs/assets"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            recording.written(),
+            ["docs/index.html", "docs/a.txt"].map(PathBuf::from).into()
+        );
+        assert!(memory.read(Path::new("docs/assets/fig.png")).is_none());
+        assert!(memory.read(Path::new("docs/a.txt")).is_some());
+    }
+}